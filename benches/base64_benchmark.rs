@@ -2,6 +2,7 @@ extern crate encrypt;
 use base64 as base64b;
 use criterion::{criterion_group, criterion_main, Criterion};
 use encrypt::base64 as base64m;
+use encrypt::base64::STANDARD;
 use encrypt::conv;
 use openssl::base64 as base64o;
 // use num_bigint::{BigInt, Sign};
@@ -35,6 +36,10 @@ fn test_openssl_base64() {
     assert_eq!(m, conv::bytes_to_string(&d.unwrap()));
 }
 
+fn payload() -> Vec<u8> {
+    (0..64 * 1024u32).map(|i| (i * 131 + i / 7) as u8).collect()
+}
+
 // fn test_mine_crate() {
 //     let m = read_string("src/aes.rs").unwrap_or_else(|_| "".to_string());
 //     let c = Base64::encode(m.as_bytes());
@@ -58,7 +63,24 @@ pub fn criterion_benchmark_base64(c: &mut Criterion) {
 }
 
 pub fn criterion_benchmark_openssl_base64(c: &mut Criterion) {
-    c.bench_function("crate", |b| b.iter(test_openssl_base64));
+    c.bench_function("openssl", |b| b.iter(test_openssl_base64));
+}
+
+// 64 KiB 的数据, 对比 SIMD 和查表
+pub fn criterion_benchmark_simd_encode(c: &mut Criterion) {
+    let m = payload();
+    let scalar = STANDARD.with_simd(false);
+    c.bench_function("encode 64K (simd)", |b| b.iter(|| STANDARD.encode(&m)));
+    c.bench_function("encode 64K (scalar)", |b| b.iter(|| scalar.encode(&m)));
+    c.bench_function("encode 64K (crate)", |b| b.iter(|| base64b::encode(&m)));
+}
+
+pub fn criterion_benchmark_simd_decode(c: &mut Criterion) {
+    let m = base64m::encode(&payload());
+    let scalar = STANDARD.with_simd(false);
+    c.bench_function("decode 64K (simd)", |b| b.iter(|| STANDARD.decode(&m)));
+    c.bench_function("decode 64K (scalar)", |b| b.iter(|| scalar.decode(&m)));
+    c.bench_function("decode 64K (crate)", |b| b.iter(|| base64b::decode(&m)));
 }
 
 // pub fn criterion_benchmark_mine_crate(c: &mut Criterion) {
@@ -74,6 +96,8 @@ criterion_group!(
     criterion_benchmark_my_base64,
     criterion_benchmark_base64,
     criterion_benchmark_openssl_base64,
+    criterion_benchmark_simd_encode,
+    criterion_benchmark_simd_decode,
     // criterion_benchmark_mine_crate,
     // criterion_benchmark_crate_mine,
 );
@@ -0,0 +1,42 @@
+extern crate encrypt;
+use criterion::{criterion_group, criterion_main, Criterion};
+use encrypt::ecc::{i_from_hex4, Ec};
+use num_bigint::BigInt;
+use num_traits::One;
+
+fn scalar() -> BigInt {
+    i_from_hex4("1E99423A 4ED27608 A15A2616 A2B0E9E5 2CED330A C530EDCC 32C8FFC6 A526AEDD").unwrap()
+}
+
+pub fn criterion_benchmark_mul(c: &mut Criterion) {
+    let ec = Ec::secp256k1();
+    let k = scalar();
+    let (_, q) = ec.gen_key();
+    c.bench_function("secp256k1 mul wnaf", |b| b.iter(|| ec.mul(&k, &q)));
+    c.bench_function("secp256k1 mul jacobian", |b| {
+        b.iter(|| ec.mul_binary(&k, &q))
+    });
+    c.bench_function("secp256k1 mul affine", |b| b.iter(|| ec.mul_affine(&k, &q)));
+    c.bench_function("secp256k1 mul_secret ladder", |b| {
+        b.iter(|| ec.mul_secret(&k, &q))
+    });
+    ec.mul_g(&BigInt::one()); // 先把 comb 表算好
+    c.bench_function("secp256k1 mul_g comb", |b| b.iter(|| ec.mul_g(&k)));
+}
+
+pub fn criterion_benchmark_sig_ver(c: &mut Criterion) {
+    let ec = Ec::secp256k1();
+    let (pri_key, pub_key) = ec.gen_key();
+    let hash_m = scalar().to_bytes_be().1;
+    let (s, pr) = ec.sig_gen(&hash_m, &pri_key);
+    c.bench_function("secp256k1 sig_ver", |b| {
+        b.iter(|| assert!(ec.sig_ver(&hash_m, &pub_key, s.clone(), pr.clone())))
+    });
+}
+
+criterion_group!(
+    benches,
+    criterion_benchmark_mul,
+    criterion_benchmark_sig_ver
+);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+extern crate encrypt;
+use criterion::{criterion_group, criterion_main, Criterion};
+use encrypt::hash::{Digest, Sha256, Sha512};
+use openssl::hash::{hash, MessageDigest};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+fn read_string(filename: impl AsRef<Path>) -> Result<String, io::Error> {
+    fs::read_to_string(filename)
+}
+
+fn make_data() -> String {
+    read_string("src/aes.rs").unwrap_or_else(|_| "".to_string())
+}
+
+fn test_my_sha256(m: &str) {
+    let d = Sha256::digest(m.as_bytes());
+    assert_eq!(d.len(), 32);
+}
+
+fn test_openssl_sha256(m: &str) {
+    let d = hash(MessageDigest::sha256(), m.as_bytes()).unwrap();
+    assert_eq!(d.len(), 32);
+}
+
+fn test_my_sha512(m: &str) {
+    let d = Sha512::digest(m.as_bytes());
+    assert_eq!(d.len(), 64);
+}
+
+fn test_openssl_sha512(m: &str) {
+    let d = hash(MessageDigest::sha512(), m.as_bytes()).unwrap();
+    assert_eq!(d.len(), 64);
+}
+
+pub fn criterion_benchmark_my_sha256(c: &mut Criterion) {
+    let m = make_data();
+    c.bench_function("mine sha256", |b| b.iter(|| test_my_sha256(&m)));
+}
+
+pub fn criterion_benchmark_openssl_sha256(c: &mut Criterion) {
+    let m = make_data();
+    c.bench_function("openssl sha256", |b| b.iter(|| test_openssl_sha256(&m)));
+}
+
+pub fn criterion_benchmark_my_sha512(c: &mut Criterion) {
+    let m = make_data();
+    c.bench_function("mine sha512", |b| b.iter(|| test_my_sha512(&m)));
+}
+
+pub fn criterion_benchmark_openssl_sha512(c: &mut Criterion) {
+    let m = make_data();
+    c.bench_function("openssl sha512", |b| b.iter(|| test_openssl_sha512(&m)));
+}
+
+criterion_group!(
+    benches,
+    criterion_benchmark_my_sha256,
+    criterion_benchmark_openssl_sha256,
+    criterion_benchmark_my_sha512,
+    criterion_benchmark_openssl_sha512,
+);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 extern crate encrypt;
 use encrypt::aes::{ByteSquare, AES};
-use encrypt::conv::hex_to_bytes;
+use encrypt::conv::hex_to_bytes_lenient;
 use openssl::aes::{aes_ige, AesKey};
 use openssl::symm::Mode;
 use std::fs;
@@ -24,15 +24,13 @@ fn main() {
     // dbg!(s.as_bytes().len());
     let blocks = s.as_bytes();
 
-    let iv1 = ByteSquare::from_col(
-        &hex_to_bytes("6D656E74 6174696F 6E206F66 20494745".replace(' ', "")).unwrap(),
-    );
-    let iv2 = ByteSquare::from_col(
-        &hex_to_bytes("206D6F64 6520666F 72204F70 656E5353".replace(' ', "")).unwrap(),
-    );
+    let iv1 =
+        ByteSquare::from_col(&hex_to_bytes_lenient("6D656E74 6174696F 6E206F66 20494745").unwrap());
+    let iv2 =
+        ByteSquare::from_col(&hex_to_bytes_lenient("206D6F64 6520666F 72204F70 656E5353").unwrap());
 
-    let mut iv_all = hex_to_bytes(
-        "6D656E74 6174696F 6E206F66 20494745 206D6F64 6520666F 72204F70 656E5353".replace(' ', ""),
+    let mut iv_all = hex_to_bytes_lenient(
+        "6D656E74 6174696F 6E206F66 20494745 206D6F64 6520666F 72204F70 656E5353",
     )
     .unwrap();
 
@@ -46,13 +44,7 @@ fn main() {
 
     let timer = std::time::Instant::now();
     let mut encrypted = vec![0; n];
-    aes_ige(
-        &blocks,
-        &mut encrypted,
-        &aes_key,
-        &mut iv_all,
-        Mode::Encrypt,
-    );
+    aes_ige(blocks, &mut encrypted, &aes_key, &mut iv_all, Mode::Encrypt);
     let t1 = timer.elapsed();
 
     let timer = std::time::Instant::now();
@@ -66,8 +58,8 @@ fn main() {
     assert_eq!(cipher.to_vec(), encrypted);
 
     let aes_key = AesKey::new_decrypt(&key).unwrap(); // NOTE: decrypt
-    let mut iv_all = hex_to_bytes(
-        "6D656E74 6174696F 6E206F66 20494745 206D6F64 6520666F 72204F70 656E5353".replace(' ', ""),
+    let mut iv_all = hex_to_bytes_lenient(
+        "6D656E74 6174696F 6E206F66 20494745 206D6F64 6520666F 72204F70 656E5353",
     )
     .unwrap();
     let timer = std::time::Instant::now();
@@ -93,7 +85,7 @@ fn main() {
     // dbg!(&decrypted[..10]);
     // dbg!(&blocks[..10]);
 
-    // dbg!(timer_all.elapsed());
+    println!("total cost: {:?}", timer_all.elapsed());
 }
 
 // fn main() {
@@ -1,6 +1,6 @@
 extern crate encrypt;
 use encrypt::ecc::{i_from_hex4, u_from_hex4, Ec, Point};
-use num_bigint::{BigInt, ToBigInt}; // BigUint, Sign,
+use num_bigint::BigInt; // BigUint, Sign, ToBigInt
 use num_primes::Generator;
 //use num_integer::{ExtendedGcd}; // It's slow
 use num_traits::Zero; // One,
@@ -11,10 +11,10 @@ fn main() {
     let x_str = "79BE667E F9DCBBAC 55A06295 CE870B07 029BFCDB 2DCE28D9 59F2815B 16F81798";
     let y_str = "483ADA77 26A3C465 5DA4FBFC 0E1108A8 FD17B448 A6855419 9C47D08F FB10D4B8";
     let n_str = "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE BAAEDCE6 AF48A03B BFD25E8C D0364141"; // the order
-    let g = Point::new(i_from_hex4(x_str), i_from_hex4(y_str));
-    let p = u_from_hex4(p_str);
-    let n = u_from_hex4(n_str);
-    let ec = Ec::new(p, BigInt::zero(), BigInt::from(7u8), n, g);
+    let g = Point::new(i_from_hex4(x_str).unwrap(), i_from_hex4(y_str).unwrap());
+    let p = u_from_hex4(p_str).unwrap();
+    let n = u_from_hex4(n_str).unwrap();
+    let ec_checked = Ec::new(p, BigInt::zero(), BigInt::from(7u8), n, g).unwrap();
     println!("gen checked secp256k1 () cost: {:?}", timer.elapsed());
 
     let timer = std::time::Instant::now();
@@ -22,7 +22,9 @@ fn main() {
     println!("gen unchecked secp256k1 cost: {:?}", timer.elapsed());
 
     let (pri_key, pub_key) = ec.gen_key();
-    let hash_m = Generator::new_uint(256).to_bigint().unwrap();
+    // 两种方式构造的曲线是同一条
+    assert_eq!(ec_checked.mul_g(&pri_key), pub_key);
+    let hash_m = Generator::new_uint(256).to_bytes_be();
 
     let timer = std::time::Instant::now();
     let (s, pr) = ec.sig_gen(&hash_m, &pri_key);
@@ -35,6 +37,9 @@ fn main() {
     let timer = std::time::Instant::now();
     let (pri_key1, pub_key1) = ec.gen_key();
     let (pri_key2, pub_key2) = ec.gen_key();
-    assert_eq!(ec.mul(&pri_key1, &pub_key2), ec.mul(&pri_key2, &pub_key1));
+    assert_eq!(
+        ec.ecdh(&pri_key1, &pub_key2).unwrap(),
+        ec.ecdh(&pri_key2, &pub_key1).unwrap()
+    );
     println!("twice ecdh cost: {:?}", timer.elapsed());
 }
@@ -3,6 +3,7 @@
 use crate::aes_const::{
     EXP_TABLE, LOG_TABLE, MIX_MAT_LOG, MIX_MAT_LOG_INV, RND_CON, SUB_BOX, SUB_BOX_INV,
 };
+use crate::error::AesError;
 use std::fmt;
 //use std::ops::{Deref, DerefMut};
 //use std::slice::rotate;
@@ -325,16 +326,20 @@ pub struct AES {
 impl AES {
     /// row style key
     pub fn new(key: &[u8]) -> Self {
+        Self::try_new(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 密钥长度不是 16/24/32 字节时返回错误
+    pub fn try_new(key: &[u8]) -> Result<Self, AesError> {
         //fn gen(&self) -> Vec<ByteSquare> {
         // NOTE: 这个密钥的生成, 没有所谓的逆过程, 就是提前算好, 然后逆序解密
-        let key_len = key.len() / N;
-
-        let round = match key_len {
-            4 => 10,
-            6 => 12,
-            8 => 14,
-            _ => panic!("AES only support 128/192/256 bits key!"),
+        let round = match key.len() {
+            16 => 10,
+            24 => 12,
+            32 => 14,
+            n => return Err(AesError::InvalidKeyLength(n)),
         };
+        let key_len = key.len() / N;
 
         let nrow = N * (round + 1);
 
@@ -381,7 +386,7 @@ impl AES {
             }
             keys.push(key); // ByteSquare { data: key });
         }
-        Self { round, keys }
+        Ok(Self { round, keys })
     }
 
     pub fn encode_ecb(&self, msg: &[u8]) -> Vec<u8> {
@@ -486,7 +491,7 @@ impl AES {
         msg.add_bytes(&self.keys[self.round]);
         msg.shift_rows_inv();
         msg.sub_inv();
-        for i in (1..self.round).into_iter().rev() {
+        for i in (1..self.round).rev() {
             msg.add_bytes(&self.keys[i]);
             msg.mix_cols_inv(cache);
             msg.shift_rows_inv();
@@ -499,7 +504,7 @@ impl AES {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::conv::hex_to_bytes;
+    use crate::conv::hex_to_bytes_lenient;
 
     // #[test]
     // fn print_log_m() {
@@ -584,7 +589,7 @@ mod test {
     #[test]
     fn test_key_manager() {
         // 128 bits
-        let a = AES::new(&(1..17).into_iter().collect::<Vec<u8>>());
+        let a = AES::new(&(1..17).collect::<Vec<u8>>());
         assert_eq!(
             [
                 0xBC, 0xC4, 0x14, 0x42, 0x6F, 0x1A, 0x5C, 0x73, 0xA1, 0x81, 0x62, 0x65, 0xB1, 0xB1,
@@ -593,7 +598,7 @@ mod test {
             a.keys[a.round]
         );
         // 256 bits
-        let a = AES::new(&(1..33).into_iter().collect::<Vec<u8>>());
+        let a = AES::new(&(1..33).collect::<Vec<u8>>());
         assert_eq!(
             [
                 0xAF, 0x06, 0x48, 0x99, 0x45, 0xED, 0x58, 0x3A, 0xAF, 0x70, 0x0C, 0xCF, 0x95, 0x76,
@@ -601,11 +606,17 @@ mod test {
             ],
             a.keys[a.round]
         );
+
+        // 17 字节以前会被当成 128 bits
+        assert!(matches!(
+            AES::try_new(&[0; 17]),
+            Err(AesError::InvalidKeyLength(17))
+        ));
     }
 
     #[test]
     fn test_ecb() {
-        let a = AES::new(&(1..33).into_iter().collect::<Vec<u8>>());
+        let a = AES::new(&(1..33).collect::<Vec<u8>>());
 
         let m = "The Advanced Encryption Standard (AES), also known by its original name Rijndael (Dutch pronunciation: [ˈrɛindaːl]),[3] is a specification for the encryption of electronic data established by the U.S. National Institute of Standards and Technology (NIST) in 2001.";
         let mut ms = m.to_string();
@@ -641,9 +652,9 @@ mod test {
     #[test]
     fn test_ige() {
         // see https://mgp25.com/AESIGE/
-        let a = AES::new(&(0..16).into_iter().collect::<Vec<u8>>());
-        let iv1 = ByteSquare::from_col(&(0..16).into_iter().collect::<Vec<u8>>());
-        let iv2 = ByteSquare::from_col(&(16..32).into_iter().collect::<Vec<u8>>());
+        let a = AES::new(&(0..16).collect::<Vec<u8>>());
+        let iv1 = ByteSquare::from_col(&(0..16).collect::<Vec<u8>>());
+        let iv2 = ByteSquare::from_col(&(16..32).collect::<Vec<u8>>());
         let block = vec![0; 32];
 
         let cipher = a.encode_ige(&block, iv1, iv2);
@@ -658,20 +669,17 @@ mod test {
             "00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000"
         );
 
-        let a = AES::new(
-            &hex_to_bytes("54686973 20697320 616E2069 6D706C65".replace(' ', "")).unwrap(),
-        );
+        let a = AES::new(&hex_to_bytes_lenient("54686973 20697320 616E2069 6D706C65").unwrap());
 
         let iv1 = ByteSquare::from_col(
-            &hex_to_bytes("6D656E74 6174696F 6E206F66 20494745".replace(' ', "")).unwrap(),
+            &hex_to_bytes_lenient("6D656E74 6174696F 6E206F66 20494745").unwrap(),
         );
         let iv2 = ByteSquare::from_col(
-            &hex_to_bytes("206D6F64 6520666F 72204F70 656E5353".replace(' ', "")).unwrap(),
+            &hex_to_bytes_lenient("206D6F64 6520666F 72204F70 656E5353").unwrap(),
         );
 
-        let block = hex_to_bytes(
-            "99706487 A1CDE613 BC6DE0B6 F24B1C7A A448C8B9 C3403E34 67A8CAD8 9340F53B"
-                .replace(' ', ""),
+        let block = hex_to_bytes_lenient(
+            "99706487 A1CDE613 BC6DE0B6 F24B1C7A A448C8B9 C3403E34 67A8CAD8 9340F53B",
         )
         .unwrap();
 
@@ -690,17 +698,14 @@ mod test {
 
     #[test]
     fn test_cbc() {
-        let a = AES::new(
-            &hex_to_bytes("54686973 20697320 616E2069 6D706C65".replace(' ', "")).unwrap(),
-        );
+        let a = AES::new(&hex_to_bytes_lenient("54686973 20697320 616E2069 6D706C65").unwrap());
 
         let iv = ByteSquare::from_col(
-            &hex_to_bytes("6D656E74 6174696F 6E206F66 20494745".replace(' ', "")).unwrap(),
+            &hex_to_bytes_lenient("6D656E74 6174696F 6E206F66 20494745").unwrap(),
         );
 
-        let block = hex_to_bytes(
-            "99706487 A1CDE613 BC6DE0B6 F24B1C7A A448C8B9 C3403E34 67A8CAD8 9340F53B"
-                .replace(' ', ""),
+        let block = hex_to_bytes_lenient(
+            "99706487 A1CDE613 BC6DE0B6 F24B1C7A A448C8B9 C3403E34 67A8CAD8 9340F53B",
         )
         .unwrap();
 
@@ -0,0 +1,259 @@
+//! Base32 (RFC 4648), base32hex (RFC 4648 §7) 和 Crockford's Base32
+//! 每 5 个字节一组, 编码成 8 个字符, 每个字符 5 bit
+
+use crate::base32_const::{
+    CROCKFORD_CHECK, CROCKFORD_CHECK_DECODE, CROCKFORD_DECODE, CROCKFORD_ENCODE, HEX_DECODE,
+    HEX_ENCODE, INVALID_VALUE, RFC4648_DECODE, RFC4648_ENCODE,
+};
+use crate::error::DecodeBase32Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alphabet {
+    /// `A-Z2-7`, TOTP 的密钥用的就是这种
+    Rfc4648,
+    /// `0-9A-V`, 编码之后的排序和原数据一致
+    Hex,
+    /// `0-9A-Z` 去掉 I L O U, 方便人读写
+    Crockford,
+}
+
+impl Alphabet {
+    #[inline]
+    fn tables(self) -> (&'static [u8; 32], &'static [u8; 256]) {
+        match self {
+            Self::Rfc4648 => (RFC4648_ENCODE, RFC4648_DECODE),
+            Self::Hex => (HEX_ENCODE, HEX_DECODE),
+            Self::Crockford => (CROCKFORD_ENCODE, CROCKFORD_DECODE),
+        }
+    }
+}
+
+/// RFC 4648, 补 `=` 到 8 的倍数
+pub fn encode(msg: &[u8]) -> Vec<u8> {
+    encode_with(msg, Alphabet::Rfc4648, true)
+}
+
+pub fn decode(msg: &[u8]) -> Result<Vec<u8>, DecodeBase32Error> {
+    decode_with(msg, Alphabet::Rfc4648)
+}
+
+pub fn encode_with(msg: &[u8], alphabet: Alphabet, padding: bool) -> Vec<u8> {
+    let (table, _) = alphabet.tables();
+    let mut res = Vec::with_capacity(msg.len().div_ceil(5) * 8);
+    for block in msg.chunks(5) {
+        // 40 bit 放在 u64 的低位
+        let mut buf = [0; 8];
+        buf[3..3 + block.len()].copy_from_slice(block);
+        let num = u64::from_be_bytes(buf);
+        let n_char = (block.len() * 8).div_ceil(5);
+        for i in 0..n_char {
+            res.push(table[(num >> (35 - 5 * i)) as usize & 0x1f]);
+        }
+        if padding {
+            res.resize(res.len() + 8 - n_char, b'=');
+        }
+    }
+    res
+}
+
+/// 大小写都可以, 末尾的 `=` 可有可无, 有的话数量必须正确;
+/// Crockford 还会忽略 `-`, 并把 O 当成 0, I 和 L 当成 1
+pub fn decode_with(msg: &[u8], alphabet: Alphabet) -> Result<Vec<u8>, DecodeBase32Error> {
+    let (_, table) = alphabet.tables();
+    let values = decode_values(msg, table, alphabet == Alphabet::Crockford)?;
+    Ok(pack(&values))
+}
+
+/// Crockford 编码, 末尾加一个校验字符: 数据当作 big endian 的整数 mod 37
+pub fn encode_crockford_check(msg: &[u8]) -> Vec<u8> {
+    let mut res = encode_with(msg, Alphabet::Crockford, false);
+    res.push(CROCKFORD_CHECK[checksum(msg) as usize]);
+    res
+}
+
+pub fn decode_crockford_check(msg: &[u8]) -> Result<Vec<u8>, DecodeBase32Error> {
+    let (&check, body) = msg
+        .split_last()
+        .ok_or(DecodeBase32Error::InvalidLength(0))?;
+    let found = match CROCKFORD_CHECK_DECODE[check as usize] {
+        INVALID_VALUE => {
+            return Err(DecodeBase32Error::InvalidChar {
+                c: check as char,
+                idx: body.len(),
+            })
+        }
+        v => v,
+    };
+    let res = pack(&decode_values(body, CROCKFORD_DECODE, true)?);
+    let expected = checksum(&res);
+    if found != expected {
+        return Err(DecodeBase32Error::InvalidChecksum {
+            expected: CROCKFORD_CHECK[expected as usize] as char,
+            found: CROCKFORD_CHECK[found as usize] as char,
+        });
+    }
+    Ok(res)
+}
+
+#[inline]
+fn checksum(msg: &[u8]) -> u8 {
+    msg.iter().fold(0u32, |r, &b| (r * 256 + b as u32) % 37) as u8
+}
+
+/// 字符转成 5 bit 的值, 同时检查长度和 padding
+fn decode_values(
+    msg: &[u8],
+    table: &[u8; 256],
+    skip_hyphen: bool,
+) -> Result<Vec<u8>, DecodeBase32Error> {
+    let n = msg.len();
+    let data_len = n - msg.iter().rev().take_while(|&&c| c == b'=').count();
+    let mut values = Vec::with_capacity(data_len);
+    for (idx, &c) in msg[..data_len].iter().enumerate() {
+        match table[c as usize] {
+            INVALID_VALUE if skip_hyphen && c == b'-' => (),
+            INVALID_VALUE if c == b'=' => return Err(DecodeBase32Error::InvalidPadding(idx)),
+            INVALID_VALUE => return Err(DecodeBase32Error::InvalidChar { c: c as char, idx }),
+            v => values.push(v),
+        }
+    }
+
+    // 最后一组只能有 2, 4, 5, 7 个字符 (对应 1 ~ 4 个字节)
+    let m = values.len();
+    if matches!(m % 8, 1 | 3 | 6) {
+        return Err(DecodeBase32Error::InvalidLength(m));
+    }
+    let pad = n - data_len;
+    if pad > 0 && pad != (8 - m % 8) % 8 {
+        return Err(DecodeBase32Error::InvalidPadding(data_len));
+    }
+    Ok(values)
+}
+
+/// 每 8 个 5 bit 的值拼成 5 个字节, 最后一组多出来的低位直接丢掉
+fn pack(values: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(values.len() * 5 / 8);
+    for block in values.chunks(8) {
+        let num = block
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, &v)| acc | (v as u64) << (35 - 5 * i));
+        let n_byte = block.len() * 5 / 8;
+        res.extend_from_slice(&num.to_be_bytes()[3..3 + n_byte]);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_base32() {
+        // RFC 4648 §10 的测试数据
+        for (m, c, h) in [
+            ("", "", ""),
+            ("f", "MY======", "CO======"),
+            ("fo", "MZXQ====", "CPNG===="),
+            ("foo", "MZXW6===", "CPNMU==="),
+            ("foob", "MZXW6YQ=", "CPNMUOG="),
+            ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+            ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+        ] {
+            assert_eq!(encode(m.as_bytes()), c.as_bytes());
+            assert_eq!(decode(c.as_bytes()).unwrap(), m.as_bytes());
+            assert_eq!(encode_with(m.as_bytes(), Alphabet::Hex, true), h.as_bytes());
+            assert_eq!(
+                decode_with(h.as_bytes(), Alphabet::Hex).unwrap(),
+                m.as_bytes()
+            );
+
+            // 不带 padding 的也能解码
+            let c = c.trim_end_matches('=');
+            assert_eq!(
+                encode_with(m.as_bytes(), Alphabet::Rfc4648, false),
+                c.as_bytes()
+            );
+            assert_eq!(decode(c.as_bytes()).unwrap(), m.as_bytes());
+        }
+
+        // TOTP 的密钥
+        assert_eq!(
+            decode(b"JBSWY3DPEHPK3PXP").unwrap(),
+            b"Hello!\xde\xad\xbe\xef"
+        );
+        assert_eq!(
+            decode(b"jbswy3dpehpk3pxp").unwrap(),
+            b"Hello!\xde\xad\xbe\xef"
+        );
+
+        assert!(matches!(
+            decode(b"MZXW8"),
+            Err(DecodeBase32Error::InvalidChar { c: '8', idx: 4 })
+        ));
+        assert!(matches!(
+            decode(b"MZX"),
+            Err(DecodeBase32Error::InvalidLength(3))
+        ));
+        assert!(matches!(
+            decode(b"MZXQ==="),
+            Err(DecodeBase32Error::InvalidPadding(4))
+        ));
+        assert!(matches!(
+            decode(b"MY=Q===="),
+            Err(DecodeBase32Error::InvalidPadding(2))
+        ));
+        assert!(matches!(
+            decode(b"MZXW1==="),
+            Err(DecodeBase32Error::InvalidChar { c: '1', idx: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_crockford() {
+        assert_eq!(
+            encode_with(b"hello", Alphabet::Crockford, false),
+            b"D1JPRV3F"
+        );
+        for c in ["D1JPRV3F", "d1jprv3f", "DIJP-RV3F", "dljp-rv3f"] {
+            assert_eq!(
+                decode_with(c.as_bytes(), Alphabet::Crockford).unwrap(),
+                b"hello"
+            );
+        }
+        assert!(matches!(
+            decode_with(b"D1JPRV3U", Alphabet::Crockford),
+            Err(DecodeBase32Error::InvalidChar { c: 'U', idx: 7 })
+        ));
+
+        assert_eq!(encode_crockford_check(b"hello"), b"D1JPRV3FJ");
+        assert_eq!(
+            encode_crockford_check(&[0xde, 0xad, 0xbe, 0xef]),
+            b"VTPVXVR5"
+        );
+        assert_eq!(decode_crockford_check(b"d1jp-rv3f-j").unwrap(), b"hello");
+        assert_eq!(
+            decode_crockford_check(b"VTPVXVR5").unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert!(matches!(
+            decode_crockford_check(b"D1JPRV3F5"),
+            Err(DecodeBase32Error::InvalidChecksum {
+                expected: 'J',
+                found: '5'
+            })
+        ));
+        assert!(matches!(
+            decode_crockford_check(b""),
+            Err(DecodeBase32Error::InvalidLength(0))
+        ));
+
+        // 5 个扩展的校验字符
+        for n in 0..200u32 {
+            let data = n.to_be_bytes();
+            let c = encode_crockford_check(&data);
+            assert_eq!(c.last(), Some(&CROCKFORD_CHECK[n as usize % 37]));
+            assert_eq!(decode_crockford_check(&c).unwrap(), data);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+pub const INVALID_VALUE: u8 = 0xFF;
+
+pub const RFC4648_ENCODE: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+pub const HEX_ENCODE: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+pub const CROCKFORD_ENCODE: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Crockford 的校验字符, 比编码用的多 5 个, 表示 mod 37 的结果
+pub const CROCKFORD_CHECK: &[u8; 37] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+pub const RFC4648_DECODE: &[u8; 256] = &make_decode_table(RFC4648_ENCODE);
+pub const HEX_DECODE: &[u8; 256] = &make_decode_table(HEX_ENCODE);
+pub const CROCKFORD_DECODE: &[u8; 256] = &make_crockford_table(CROCKFORD_ENCODE);
+pub const CROCKFORD_CHECK_DECODE: &[u8; 256] = &make_crockford_table(CROCKFORD_CHECK);
+
+/// 编译期由字母表生成反查表, 大小写都可以
+const fn make_decode_table<const N: usize>(alphabet: &[u8; N]) -> [u8; 256] {
+    let mut table = [INVALID_VALUE; 256];
+    let mut i = 0;
+    while i < N {
+        table[alphabet[i] as usize] = i as u8;
+        table[alphabet[i].to_ascii_lowercase() as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Crockford 还要把容易看错的 O 当成 0, I 和 L 当成 1
+const fn make_crockford_table<const N: usize>(alphabet: &[u8; N]) -> [u8; 256] {
+    let mut table = make_decode_table(alphabet);
+    table[b'O' as usize] = 0;
+    table[b'o' as usize] = 0;
+    table[b'I' as usize] = 1;
+    table[b'i' as usize] = 1;
+    table[b'L' as usize] = 1;
+    table[b'l' as usize] = 1;
+    table
+}
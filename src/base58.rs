@@ -0,0 +1,209 @@
+//! Base58 (比特币地址, WIF 私钥) 和 Base58Check (末尾加 4 字节 hash256 校验和)
+
+use crate::base58_const::{
+    BITCOIN_DECODE, BITCOIN_ENCODE, FLICKR_DECODE, FLICKR_ENCODE, INVALID_VALUE, RIPPLE_DECODE,
+    RIPPLE_ENCODE,
+};
+use crate::error::DecodeBase58Error;
+use crate::hash::hash256;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alphabet {
+    Bitcoin,
+    Ripple,
+    Flickr,
+}
+
+impl Alphabet {
+    #[inline]
+    fn tables(self) -> (&'static [u8; 58], &'static [u8; 256]) {
+        match self {
+            Self::Bitcoin => (BITCOIN_ENCODE, BITCOIN_DECODE),
+            Self::Ripple => (RIPPLE_ENCODE, RIPPLE_DECODE),
+            Self::Flickr => (FLICKR_ENCODE, FLICKR_DECODE),
+        }
+    }
+}
+
+pub fn encode(msg: &[u8]) -> Vec<u8> {
+    encode_with(msg, Alphabet::Bitcoin)
+}
+
+pub fn decode(msg: &[u8]) -> Result<Vec<u8>, DecodeBase58Error> {
+    decode_with(msg, Alphabet::Bitcoin)
+}
+
+pub fn encode_with(msg: &[u8], alphabet: Alphabet) -> Vec<u8> {
+    let (table, _) = alphabet.tables();
+    // 开头的每个 0 字节对应一个 '1' (字母表的第一个字符)
+    let n_zero = msg.iter().take_while(|&&v| v == 0).count();
+
+    // 大数除法: 256 进制转 58 进制, 结果倒序存放
+    // log(256) / log(58) ~= 1.37
+    let mut digits: Vec<u8> = Vec::with_capacity((msg.len() - n_zero) * 138 / 100 + 1);
+    for &v in &msg[n_zero..] {
+        let mut carry = v as usize;
+        for d in digits.iter_mut() {
+            carry += (*d as usize) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut res = vec![table[0]; n_zero];
+    res.extend(digits.iter().rev().map(|&d| table[d as usize]));
+    res
+}
+
+pub fn decode_with(msg: &[u8], alphabet: Alphabet) -> Result<Vec<u8>, DecodeBase58Error> {
+    let (table, decode_table) = alphabet.tables();
+    let n_zero = msg.iter().take_while(|&&c| c == table[0]).count();
+
+    // 58 进制转 256 进制, 结果倒序存放
+    let mut bytes: Vec<u8> = Vec::with_capacity((msg.len() - n_zero) * 733 / 1000 + 1);
+    for (i, &c) in msg.iter().enumerate().skip(n_zero) {
+        let mut carry = match decode_table[c as usize] {
+            INVALID_VALUE => {
+                return Err(DecodeBase58Error::InvalidChar {
+                    c: c as char,
+                    idx: i,
+                })
+            }
+            d => d as usize,
+        };
+        for b in bytes.iter_mut() {
+            carry += *b as usize * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut res = vec![0; n_zero];
+    res.extend(bytes.iter().rev());
+    Ok(res)
+}
+
+/// payload || hash256(payload)[..4]
+pub fn encode_check(payload: &[u8]) -> Vec<u8> {
+    encode_check_with(payload, Alphabet::Bitcoin)
+}
+
+/// 返回去掉校验和之后的 payload
+pub fn decode_check(msg: &[u8]) -> Result<Vec<u8>, DecodeBase58Error> {
+    decode_check_with(msg, Alphabet::Bitcoin)
+}
+
+pub fn encode_check_with(payload: &[u8], alphabet: Alphabet) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 4);
+    data.extend(payload);
+    data.extend(&hash256(payload)[..4]);
+    encode_with(&data, alphabet)
+}
+
+pub fn decode_check_with(msg: &[u8], alphabet: Alphabet) -> Result<Vec<u8>, DecodeBase58Error> {
+    let mut data = decode_with(msg, alphabet)?;
+    if data.len() < 4 {
+        return Err(DecodeBase58Error::TooShort(data.len()));
+    }
+    let n = data.len() - 4;
+    let expected: [u8; 4] = hash256(&data[..n])[..4].try_into().unwrap();
+    let found: [u8; 4] = data[n..].try_into().unwrap();
+    if expected != found {
+        return Err(DecodeBase58Error::InvalidChecksum { expected, found });
+    }
+    data.truncate(n);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::{self, hex_to_bytes};
+
+    #[test]
+    fn test_base58() {
+        assert_eq!(
+            "2NEpo7TZRRrLZSi2U",
+            conv::bytes_to_string(&encode(b"Hello World!"))
+        );
+        assert_eq!(
+            b"Hello World!".to_vec(),
+            decode(b"2NEpo7TZRRrLZSi2U").unwrap()
+        );
+
+        let bytes = hex_to_bytes("0000287fb4cd").unwrap();
+        assert_eq!("11233QC4", conv::bytes_to_string(&encode(&bytes)));
+        assert_eq!(bytes, decode(b"11233QC4").unwrap());
+
+        assert!(encode(b"").is_empty());
+        assert!(decode(b"").unwrap().is_empty());
+        assert_eq!(vec![0, 0], decode(b"11").unwrap());
+
+        assert!(matches!(
+            decode(b"2NEpo7TZRRrLZSi0U"),
+            Err(DecodeBase58Error::InvalidChar { c: '0', idx: 15 })
+        ));
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let msg = b"hello world";
+        for alphabet in [Alphabet::Bitcoin, Alphabet::Ripple, Alphabet::Flickr] {
+            let c = encode_with(msg, alphabet);
+            assert_eq!(decode_with(&c, alphabet).unwrap(), msg);
+        }
+        assert_eq!(
+            "StV1DL6CwTryKyV",
+            conv::bytes_to_string(&encode_with(msg, Alphabet::Bitcoin))
+        );
+        assert_eq!(
+            "rTu1dk6cWsRYjYu",
+            conv::bytes_to_string(&encode_with(msg, Alphabet::Flickr))
+        );
+
+        // 瑞波的 ACCOUNT_ZERO: 版本号 0x00 || 20 个 0
+        let c = encode_check_with(&[0; 21], Alphabet::Ripple);
+        assert_eq!("rrrrrrrrrrrrrrrrrrrrrhoLvTp", conv::bytes_to_string(&c));
+        assert_eq!(
+            vec![0; 21],
+            decode_check_with(&c, Alphabet::Ripple).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_base58_check() {
+        // 私钥的 WIF 格式: 0x80 || private key
+        let mut payload = vec![0x80];
+        payload.extend(
+            hex_to_bytes("0C28FCA386C7A227600B2FE50B7CAE11EC86D3BF1FBE471BE89827E19D72AA1D")
+                .unwrap(),
+        );
+        let wif = "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ";
+        assert_eq!(wif, conv::bytes_to_string(&encode_check(&payload)));
+        assert_eq!(payload, decode_check(wif.as_bytes()).unwrap());
+
+        // P2PKH 地址: 0x00 || hash160(pub_key)
+        let mut payload = vec![0x00];
+        payload.extend(hex_to_bytes("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap());
+        let addr = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+        assert_eq!(addr, conv::bytes_to_string(&encode_check(&payload)));
+        assert_eq!(payload, decode_check(addr.as_bytes()).unwrap());
+
+        assert!(matches!(
+            decode_check(b"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMJ"),
+            Err(DecodeBase58Error::InvalidChecksum { .. })
+        ));
+        assert!(matches!(
+            decode_check(b"2"),
+            Err(DecodeBase58Error::TooShort(1))
+        ));
+    }
+}
@@ -0,0 +1,20 @@
+pub const INVALID_VALUE: u8 = 0xFF;
+
+pub const BITCOIN_ENCODE: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+pub const RIPPLE_ENCODE: &[u8; 58] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+pub const FLICKR_ENCODE: &[u8; 58] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+
+pub const BITCOIN_DECODE: &[u8; 256] = &make_decode_table(BITCOIN_ENCODE);
+pub const RIPPLE_DECODE: &[u8; 256] = &make_decode_table(RIPPLE_ENCODE);
+pub const FLICKR_DECODE: &[u8; 256] = &make_decode_table(FLICKR_ENCODE);
+
+/// 编译期由字母表生成反查表
+const fn make_decode_table(alphabet: &[u8; 58]) -> [u8; 256] {
+    let mut table = [INVALID_VALUE; 256];
+    let mut i = 0;
+    while i < 58 {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
@@ -1,86 +1,619 @@
-use crate::base64_const::{INVALID_VALUE, STANDARD_DECODE, STANDARD_ENCODE};
-use crate::error::DecodeBase64Error;
+use crate::base64_const::{
+    BCRYPT_DECODE, BCRYPT_ENCODE, CRYPT_DECODE, CRYPT_ENCODE, INVALID_VALUE, STANDARD_DECODE,
+    STANDARD_ENCODE, URL_SAFE_DECODE, URL_SAFE_ENCODE,
+};
+use crate::base64_simd;
+use crate::error::{ConvError, DecodeBase64Error};
+use std::io::{self, Read, Write};
 
-pub fn encode(msg: &[u8]) -> Vec<u8> {
-    let n = msg.len();
-    let n_block = n / 3;
-    let m = match n % 3 {
-        0 => n_block << 2,
-        _ => (n_block + 1) << 2,
-    };
-    let mut res = vec![0; m];
-
-    let mut msg_i = 0;
-    let mut res_i = 0;
-    for _ in 0..n_block {
-        // NOTE: [lo..hi] 这种形式可以减少 bound check, 提高速度
-        encode_3bytes(&msg[msg_i..msg_i + 3], &mut res[res_i..res_i + 4]);
-        msg_i += 3;
-        res_i += 4;
-    }
-
-    match n % 3 {
-        0 => (),
-        x => {
+/// 字母表
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// RFC 4648, `+` 和 `/`
+    Standard,
+    /// RFC 4648 §5, `-` 和 `_`, 用于 URL, 文件名和 JWT
+    UrlSafe,
+    /// bcrypt 用的, `./A-Za-z0-9`
+    Bcrypt,
+    /// crypt(3) 用的, `./0-9A-Za-z`
+    Crypt,
+}
+
+impl Alphabet {
+    const fn tables(self) -> (&'static [u8; 64], &'static [u8; 256]) {
+        match self {
+            Alphabet::Standard => (STANDARD_ENCODE, STANDARD_DECODE),
+            Alphabet::UrlSafe => (URL_SAFE_ENCODE, URL_SAFE_DECODE),
+            Alphabet::Bcrypt => (BCRYPT_ENCODE, BCRYPT_DECODE),
+            Alphabet::Crypt => (CRYPT_ENCODE, CRYPT_DECODE),
+        }
+    }
+}
+
+/// 末尾 `=` 的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    /// 编码时补 `=`, 解码时长度必须是 4 的倍数
+    Required,
+    /// 编码时不补, 解码时有没有都可以
+    Optional,
+    /// 编码时不补, 解码时遇到 `=` 报错
+    Forbidden,
+}
+
+/// 换行的时候用的换行符
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    LF,
+    CRLF,
+}
+
+impl LineEnding {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::LF => b"\n",
+            LineEnding::CRLF => b"\r\n",
+        }
+    }
+}
+
+/// PEM (RFC 7468) 每行 64 个字符
+pub const PEM_LINE_LEN: usize = 64;
+/// MIME (RFC 2045) 每行最多 76 个字符
+pub const MIME_LINE_LEN: usize = 76;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Base64Engine {
+    alphabet: Alphabet,
+    encode_table: &'static [u8; 64],
+    decode_table: &'static [u8; 256],
+    padding: Padding,
+    strict: bool,
+    simd: bool,
+}
+
+pub const STANDARD: Base64Engine = Base64Engine::new(Alphabet::Standard, Padding::Required);
+pub const STANDARD_NO_PAD: Base64Engine = Base64Engine::new(Alphabet::Standard, Padding::Forbidden);
+pub const URL_SAFE: Base64Engine = Base64Engine::new(Alphabet::UrlSafe, Padding::Required);
+pub const URL_SAFE_NO_PAD: Base64Engine = Base64Engine::new(Alphabet::UrlSafe, Padding::Forbidden);
+
+impl Base64Engine {
+    pub const fn new(alphabet: Alphabet, padding: Padding) -> Self {
+        let (encode_table, decode_table) = alphabet.tables();
+        Self {
+            alphabet,
+            encode_table,
+            decode_table,
+            padding,
+            strict: false,
+            simd: true,
+        }
+    }
+
+    /// 默认在运行时检测 CPU 是否支持 SSSE3 / AVX2, 支持的话用 SIMD 处理中间完整的分组,
+    /// 关掉之后只用查表, 主要是测试和 benchmark 用
+    pub const fn with_simd(mut self, simd: bool) -> Self {
+        self.simd = simd;
+        self
+    }
+
+    /// 严格模式: 最后一个字符多出来的低位必须为 0, 保证每段数据只有一种编码 (防止签名数据被篡改)
+    pub const fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn padding(&self) -> Padding {
+        self.padding
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// 编码之后的长度
+    pub fn encoded_len(&self, n: usize) -> usize {
+        encoded_len(n, self.padding == Padding::Required)
+    }
+
+    pub fn encode(&self, msg: &[u8]) -> Vec<u8> {
+        let mut res = vec![0; self.encoded_len(msg.len())];
+        self.encode_to(msg, &mut res);
+        res
+    }
+
+    /// 编码到 out 中, 返回写入的长度; out 的长度至少要是 encoded_len
+    pub fn encode_into(&self, msg: &[u8], out: &mut [u8]) -> Result<usize, ConvError> {
+        let needed = self.encoded_len(msg.len());
+        if out.len() < needed {
+            return Err(ConvError::OutputTooSmall {
+                len: out.len(),
+                needed,
+            });
+        }
+        Ok(self.encode_to(msg, out))
+    }
+
+    /// out 的长度已经检查过了
+    fn encode_to(&self, msg: &[u8], out: &mut [u8]) -> usize {
+        let n = msg.len();
+        let n_block = n / 3;
+        let rem = n % 3;
+        let m = self.encoded_len(n);
+
+        let (mut msg_i, mut res_i) = match self.simd {
+            true => {
+                base64_simd::encode(&msg[..n_block * 3], &mut out[..n_block * 4], self.alphabet)
+            }
+            false => (0, 0),
+        };
+        for _ in msg_i / 3..n_block {
+            // NOTE: [lo..hi] 这种形式可以减少 bound check, 提高速度
+            encode_3bytes(
+                &msg[msg_i..msg_i + 3],
+                &mut out[res_i..res_i + 4],
+                self.encode_table,
+            );
+            msg_i += 3;
+            res_i += 4;
+        }
+
+        if rem != 0 {
             let mut last = [0; 3];
-            last[0..x].copy_from_slice(&msg[n - x..n]);
-            encode_3bytes(&last, &mut res[n_block * 4..m]);
-            for i in x..3 {
-                res[m - 3 + i] = b'=';
+            last[..rem].copy_from_slice(&msg[n - rem..n]);
+            let mut tail = [b'='; 4];
+            encode_3bytes(&last, &mut tail, self.encode_table);
+            tail[rem + 1..].fill(b'=');
+            out[res_i..m].copy_from_slice(&tail[..m - res_i]);
+        }
+        m
+    }
+
+    pub fn decode(&self, msg: &[u8]) -> Result<Vec<u8>, DecodeBase64Error> {
+        let mut res = vec![0; decoded_len_estimate(msg.len())];
+        let m = self.decode_into(msg, &mut res)?;
+        res.truncate(m);
+        Ok(res)
+    }
+
+    /// 解码到 out 中, 返回写入的长度.
+    /// out 的长度不小于 decoded_len_estimate 时一定够用, 不够的话返回 OutputTooSmall
+    pub fn decode_into(&self, msg: &[u8], out: &mut [u8]) -> Result<usize, DecodeBase64Error> {
+        let n = msg.len();
+        if n == 0 || (self.padding == Padding::Required && n % 4 != 0) {
+            return Err(DecodeBase64Error::InvalidLength(n));
+        }
+        // `=` 只能出现在末尾, 最多两个, 而且补齐之后长度是 4 的倍数
+        let body_len = n - msg.iter().rev().take_while(|&&c| c == b'=').count();
+        let pad = n - body_len;
+        if pad > 2 || (pad > 0 && (self.padding == Padding::Forbidden || n % 4 != 0)) {
+            return Err(DecodeBase64Error::InvalidPadding(body_len));
+        }
+        if body_len % 4 == 1 {
+            return Err(DecodeBase64Error::InvalidLength(n));
+        }
+
+        let body = &msg[..body_len];
+        let n_block = body.len() / 4;
+        let rem = body.len() % 4;
+        let m = n_block * 3 + rem.saturating_sub(1);
+        if out.len() < m {
+            return Err(DecodeBase64Error::OutputTooSmall {
+                len: out.len(),
+                needed: m,
+            });
+        }
+        let out = &mut out[..m];
+
+        let (mut msg_i, mut res_i) = match self.simd {
+            true => base64_simd::decode(&body[..n_block * 4], out, self.alphabet),
+            false => (0, 0),
+        };
+        // decode_4bytes 每次写 4 个字节, 没有剩余字符时最后一组要先写到临时数组里
+        let n_direct = match rem {
+            0 => n_block.saturating_sub(1),
+            _ => n_block,
+        };
+        for _ in msg_i / 4..n_direct {
+            decode_4bytes(
+                &body[msg_i..msg_i + 4],
+                &mut out[res_i..res_i + 4],
+                msg_i,
+                self.decode_table,
+            )?;
+            msg_i += 4;
+            res_i += 3;
+        }
+
+        let mut last = [0; 4];
+        if msg_i < n_block * 4 {
+            decode_4bytes(&body[msg_i..msg_i + 4], &mut last, msg_i, self.decode_table)?;
+            out[res_i..res_i + 3].copy_from_slice(&last[..3]);
+            msg_i += 4;
+            res_i += 3;
+        }
+
+        if rem != 0 {
+            // 不足 4 个字符的用值为 0 的字符补齐
+            let mut tail = [self.encode_table[0]; 4];
+            tail[..rem].copy_from_slice(&body[msg_i..]);
+            decode_4bytes(&tail, &mut last, msg_i, self.decode_table)?;
+            out[res_i..m].copy_from_slice(&last[..rem - 1]);
+
+            // 2 个字符剩 4 位, 3 个字符剩 2 位
+            let idx = msg_i + rem - 1;
+            let mask = if rem == 2 { 0x0f } else { 0x03 };
+            if self.strict && self.decode_table[body[idx] as usize] & mask != 0 {
+                return Err(DecodeBase64Error::NonCanonical(idx));
+            }
+        }
+        Ok(m)
+    }
+
+    /// 每 line_len 个字符换一行, 最后一行后面不加换行符
+    pub fn encode_wrapped(&self, msg: &[u8], line_len: usize, ending: LineEnding) -> Vec<u8> {
+        assert!(line_len > 0);
+        let body = self.encode(msg);
+        let ending = ending.as_bytes();
+        let n_line = body.len().div_ceil(line_len);
+        let mut res = Vec::with_capacity(body.len() + n_line.saturating_sub(1) * ending.len());
+        for (i, line) in body.chunks(line_len).enumerate() {
+            if i > 0 {
+                res.extend_from_slice(ending);
             }
+            res.extend_from_slice(line);
         }
+        res
+    }
+
+    /// 跳过空白字符 (空格, `\t`, `\r`, `\n` 等) 之后再解码, 报错的位置是在原输入中的位置
+    pub fn decode_lenient(&self, msg: &[u8]) -> Result<Vec<u8>, DecodeBase64Error> {
+        let body: Vec<u8> = msg
+            .iter()
+            .copied()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        if body.len() == msg.len() {
+            return self.decode(msg);
+        }
+
+        // 去掉空白之后的第 k 个字符在原输入中的位置
+        let origin = |k: usize| {
+            msg.iter()
+                .enumerate()
+                .filter(|(_, c)| !c.is_ascii_whitespace())
+                .nth(k)
+                .map_or(msg.len(), |(i, _)| i)
+        };
+        self.decode(&body).map_err(|e| match e {
+            DecodeBase64Error::InvalidChar { c, idx } => DecodeBase64Error::InvalidChar {
+                c,
+                idx: origin(idx),
+            },
+            DecodeBase64Error::InvalidPadding(idx) => {
+                DecodeBase64Error::InvalidPadding(origin(idx))
+            }
+            DecodeBase64Error::NonCanonical(idx) => DecodeBase64Error::NonCanonical(origin(idx)),
+            e => e,
+        })
     }
-    res
 }
 
-#[inline]
-fn encode_3bytes(msg: &[u8], res: &mut [u8]) {
-    let num = (msg[0] as usize) << 16 | (msg[1] as usize) << 8 | msg[2] as usize;
-    res[0] = STANDARD_ENCODE[(num >> 18) & 0x3f];
-    res[1] = STANDARD_ENCODE[(num >> 12) & 0x3f];
-    res[2] = STANDARD_ENCODE[(num >> 6) & 0x3f];
-    res[3] = STANDARD_ENCODE[num & 0x3f];
+pub fn encode(msg: &[u8]) -> Vec<u8> {
+    STANDARD.encode(msg)
 }
 
 pub fn decode(msg: &[u8]) -> Result<Vec<u8>, DecodeBase64Error> {
-    let n = msg.len();
-    if n % 4 != 0 || n == 0 {
-        return Err(DecodeBase64Error::InvalidLength(n));
+    STANDARD.decode(msg)
+}
+
+/// 标准字母表, 不需要分配内存
+pub fn encode_into(msg: &[u8], out: &mut [u8]) -> Result<usize, ConvError> {
+    STANDARD.encode_into(msg, out)
+}
+
+pub fn decode_into(msg: &[u8], out: &mut [u8]) -> Result<usize, DecodeBase64Error> {
+    STANDARD.decode_into(msg, out)
+}
+
+/// n 个字节编码之后的长度
+pub const fn encoded_len(n: usize, padding: bool) -> usize {
+    match (n % 3, padding) {
+        (0, _) => n / 3 * 4,
+        (_, true) => (n / 3 + 1) * 4,
+        (x, false) => n / 3 * 4 + x + 1,
+    }
+}
+
+/// n 个字符解码之后长度的上界, 不考虑 padding 和空白
+pub const fn decoded_len_estimate(n: usize) -> usize {
+    n.div_ceil(4) * 3
+}
+
+/// PEM 格式: 每行 64 个字符, `\n` 换行
+pub fn encode_pem(msg: &[u8]) -> Vec<u8> {
+    STANDARD.encode_wrapped(msg, PEM_LINE_LEN, LineEnding::LF)
+}
+
+/// MIME 格式: 每行 76 个字符, `\r\n` 换行
+pub fn encode_mime(msg: &[u8]) -> Vec<u8> {
+    STANDARD.encode_wrapped(msg, MIME_LINE_LEN, LineEnding::CRLF)
+}
+
+/// 标准字母表, 忽略空白字符, 可以直接解码 PEM 和邮件附件
+pub fn decode_lenient(msg: &[u8]) -> Result<Vec<u8>, DecodeBase64Error> {
+    STANDARD.decode_lenient(msg)
+}
+
+/// 标准字母表, 严格模式
+pub fn decode_strict(msg: &[u8]) -> Result<Vec<u8>, DecodeBase64Error> {
+    STANDARD.with_strict(true).decode(msg)
+}
+
+/// 每次最多处理的分组数, 避免大块写入时一次分配太多内存
+const STREAM_GROUPS: usize = 1024;
+
+/// 边写边编码, 不足 3 字节的部分留到下一次 write, 最后由 finish (或 drop) 写出
+pub struct Base64Writer<W: Write> {
+    inner: Option<W>,
+    engine: Base64Engine,
+    buf: [u8; 3],
+    len: usize,
+}
+
+impl<W: Write> Base64Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_engine(inner, STANDARD)
+    }
+
+    pub fn with_engine(inner: W, engine: Base64Engine) -> Self {
+        Self {
+            inner: Some(inner),
+            engine,
+            buf: [0; 3],
+            len: 0,
+        }
+    }
+
+    /// 写出最后不足 3 字节的部分 (以及 padding), 返回内部的 writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_tail()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn write_tail(&mut self) -> io::Result<()> {
+        if let Some(inner) = self.inner.as_mut() {
+            if self.len > 0 {
+                let tail = self.engine.encode(&self.buf[..self.len]);
+                self.len = 0;
+                inner.write_all(&tail)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Base64Writer<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len();
+        let table = self.engine.encode_table;
+        let inner = self.inner.as_mut().unwrap();
+        let mut out = [0; STREAM_GROUPS * 4];
+
+        // 先把上次剩下的凑成一组
+        let mut data = data;
+        if self.len > 0 {
+            let k = (3 - self.len).min(data.len());
+            self.buf[self.len..self.len + k].copy_from_slice(&data[..k]);
+            self.len += k;
+            data = &data[k..];
+            if self.len < 3 {
+                return Ok(n);
+            }
+            encode_3bytes(&self.buf, &mut out[..4], table);
+            self.len = 0;
+            inner.write_all(&out[..4])?;
+        }
+
+        let mut chunks = data.chunks_exact(3);
+        loop {
+            let mut out_i = 0;
+            for block in chunks.by_ref().take(STREAM_GROUPS) {
+                encode_3bytes(block, &mut out[out_i..out_i + 4], table);
+                out_i += 4;
+            }
+            if out_i == 0 {
+                break;
+            }
+            inner.write_all(&out[..out_i])?;
+        }
+        let rest = chunks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+        Ok(n)
+    }
+
+    /// 不会写出不足 3 字节的部分, 否则后面的数据就接不上了
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for Base64Writer<W> {
+    fn drop(&mut self) {
+        // drop 的时候没法报错, 需要错误信息的话用 finish
+        let _ = self.write_tail();
+    }
+}
+
+/// 边读边解码, 和 decode_lenient 一样跳过空白字符, 出错时返回 `io::ErrorKind::InvalidData`,
+/// 里面是带有原输入位置的 DecodeBase64Error
+pub struct Base64Reader<R: Read> {
+    inner: R,
+    engine: Base64Engine,
+    quad: [u8; 4],
+    quad_idx: [usize; 4], // quad 中每个字符在原输入中的位置
+    quad_len: usize,
+    consumed: usize,        // 已经读入的字节数, 包括空白
+    n_char: usize,          // 不算空白的字符数
+    pad_idx: Option<usize>, // 已经读到带 `=` 的最后一组, 后面不能再有数据
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    done: bool,
+}
+
+impl<R: Read> Base64Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_engine(inner, STANDARD)
+    }
+
+    pub fn with_engine(inner: R, engine: Base64Engine) -> Self {
+        Self {
+            inner,
+            engine,
+            quad: [0; 4],
+            quad_idx: [0; 4],
+            quad_len: 0,
+            consumed: 0,
+            n_char: 0,
+            pad_idx: None,
+            decoded: Vec::with_capacity(STREAM_GROUPS * 3),
+            decoded_pos: 0,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// 从 inner 读一块数据解码到 decoded 中
+    fn fill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.decoded_pos = 0;
+
+        let mut raw = [0; STREAM_GROUPS * 4];
+        let n = loop {
+            match self.inner.read(&mut raw) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+                Ok(n) => break n,
+            }
+        };
+        if n == 0 {
+            self.done = true;
+            return self.decode_tail().map_err(invalid_data);
+        }
+
+        for &c in &raw[..n] {
+            let idx = self.consumed;
+            self.consumed += 1;
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            if let Some(pad_idx) = self.pad_idx {
+                return Err(invalid_data(DecodeBase64Error::InvalidPadding(pad_idx)));
+            }
+            self.n_char += 1;
+            self.quad[self.quad_len] = c;
+            self.quad_idx[self.quad_len] = idx;
+            self.quad_len += 1;
+            if self.quad_len < 4 {
+                continue;
+            }
+
+            if let Some(i) = self.quad.iter().position(|&c| c == b'=') {
+                // 带 padding 的只能是最后一组
+                self.pad_idx = Some(self.quad_idx[i]);
+                self.decode_tail().map_err(invalid_data)?;
+            } else {
+                let mut out = [0; 4];
+                decode_4bytes(&self.quad, &mut out, 0, self.engine.decode_table)
+                    .map_err(|e| invalid_data(self.remap(e)))?;
+                self.decoded.extend_from_slice(&out[..3]);
+            }
+            self.quad_len = 0;
+        }
+        Ok(())
     }
-    let n_block = n / 4;
-    let mut res = vec![0; n / 4 * 3 + 1];
 
-    let mut msg_i = 0;
-    let mut res_i = 0;
-    for _ in 0..n_block {
-        decode_4bytes(&msg[msg_i..msg_i + 4], &mut res[res_i..res_i + 4], msg_i)?;
-        msg_i += 4;
-        res_i += 3;
+    /// 最后一组交给 Base64Engine::decode, padding 和严格模式的规则都一样
+    fn decode_tail(&mut self) -> Result<(), DecodeBase64Error> {
+        if self.quad_len > 0 {
+            let tail = self
+                .engine
+                .decode(&self.quad[..self.quad_len])
+                .map_err(|e| self.remap(e))?;
+            self.decoded.extend_from_slice(&tail);
+        }
+        Ok(())
     }
 
-    if b'=' == msg[n - 2] {
-        res.pop();
+    /// quad 中的位置换成原输入中的位置
+    fn remap(&self, e: DecodeBase64Error) -> DecodeBase64Error {
+        let origin = |i: usize| self.quad_idx.get(i).copied().unwrap_or(self.consumed);
+        match e {
+            DecodeBase64Error::InvalidLength(_) => DecodeBase64Error::InvalidLength(self.n_char),
+            DecodeBase64Error::InvalidChar { c, idx } => DecodeBase64Error::InvalidChar {
+                c,
+                idx: origin(idx),
+            },
+            DecodeBase64Error::InvalidPadding(idx) => {
+                DecodeBase64Error::InvalidPadding(origin(idx))
+            }
+            DecodeBase64Error::NonCanonical(idx) => DecodeBase64Error::NonCanonical(origin(idx)),
+            e @ DecodeBase64Error::OutputTooSmall { .. } => e,
+        }
     }
-    if b'=' == msg[n - 1] {
-        res.pop();
+}
+
+impl<R: Read> Read for Base64Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded_pos == self.decoded.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = buf.len().min(self.decoded.len() - self.decoded_pos);
+        buf[..n].copy_from_slice(&self.decoded[self.decoded_pos..self.decoded_pos + n]);
+        self.decoded_pos += n;
+        Ok(n)
     }
-    res.pop();
-    Ok(res)
+}
+
+fn invalid_data(e: DecodeBase64Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
 }
 
 #[inline]
-fn decode_4bytes(msg: &[u8], res: &mut [u8], i: usize) -> Result<(), DecodeBase64Error> {
-    let num = decode_char(msg[0], i)? << 26
-        | decode_char(msg[1], i + 1)? << 20
-        | decode_char(msg[2], i + 2)? << 14
-        | decode_char(msg[3], i + 3)? << 8;
+fn encode_3bytes(msg: &[u8], res: &mut [u8], table: &[u8; 64]) {
+    let num = (msg[0] as usize) << 16 | (msg[1] as usize) << 8 | msg[2] as usize;
+    res[0] = table[(num >> 18) & 0x3f];
+    res[1] = table[(num >> 12) & 0x3f];
+    res[2] = table[(num >> 6) & 0x3f];
+    res[3] = table[num & 0x3f];
+}
+
+#[inline]
+fn decode_4bytes(
+    msg: &[u8],
+    res: &mut [u8],
+    i: usize,
+    table: &[u8; 256],
+) -> Result<(), DecodeBase64Error> {
+    let num = decode_char(msg[0], i, table)? << 26
+        | decode_char(msg[1], i + 1, table)? << 20
+        | decode_char(msg[2], i + 2, table)? << 14
+        | decode_char(msg[3], i + 3, table)? << 8;
     res[..4].copy_from_slice(&num.to_be_bytes());
     Ok(())
 }
 
 #[inline]
-fn decode_char(v: u8, idx: usize) -> Result<u32, DecodeBase64Error> {
-    match STANDARD_DECODE[v as usize] {
+fn decode_char(v: u8, idx: usize, table: &[u8; 256]) -> Result<u32, DecodeBase64Error> {
+    match table[v as usize] {
+        INVALID_VALUE if v == b'=' => Err(DecodeBase64Error::InvalidPadding(idx)),
         INVALID_VALUE => Err(DecodeBase64Error::InvalidChar { c: v as char, idx }),
         d => Ok(d as u32),
     }
@@ -129,4 +662,338 @@ mod test {
             conv::bytes_to_string(&decode(b"aGVsbG8gd29ybGQhIQ==").unwrap())
         );
     }
+
+    #[test]
+    fn test_engine() {
+        let data = [0xfb, 0xff, 0xbf];
+        assert_eq!(encode(&data[..2]), b"+/8=");
+        assert_eq!(URL_SAFE.encode(&data[..2]), b"-_8=");
+        assert_eq!(URL_SAFE_NO_PAD.encode(&data[..2]), b"-_8");
+        assert_eq!(URL_SAFE_NO_PAD.encode(&data), b"-_-_");
+        assert_eq!(URL_SAFE_NO_PAD.decode(b"-_8").unwrap(), &data[..2]);
+
+        // JWT 的 header
+        let header = br#"{"alg":"HS256","typ":"JWT"}"#;
+        let token = b"eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9";
+        assert_eq!(URL_SAFE_NO_PAD.encode(header), token);
+        assert_eq!(URL_SAFE_NO_PAD.decode(token).unwrap(), header);
+
+        // 不同的 padding 规则
+        let optional = Base64Engine::new(Alphabet::UrlSafe, Padding::Optional);
+        assert_eq!(optional.padding(), Padding::Optional);
+        assert_eq!(optional.encode(b"a"), b"YQ");
+        assert_eq!(optional.decode(b"YQ").unwrap(), b"a");
+        assert_eq!(optional.decode(b"YQ==").unwrap(), b"a");
+        assert!(matches!(
+            optional.decode(b"YQ="),
+            Err(DecodeBase64Error::InvalidPadding(2))
+        ));
+        assert!(matches!(
+            URL_SAFE_NO_PAD.decode(b"YQ=="),
+            Err(DecodeBase64Error::InvalidPadding(2))
+        ));
+        assert!(matches!(
+            STANDARD.decode(b"YQ"),
+            Err(DecodeBase64Error::InvalidLength(2))
+        ));
+        assert!(matches!(
+            URL_SAFE_NO_PAD.decode(b"YWJjZ"),
+            Err(DecodeBase64Error::InvalidLength(5))
+        ));
+        assert!(matches!(
+            URL_SAFE.decode(b"+/8="),
+            Err(DecodeBase64Error::InvalidChar { c: '+', idx: 0 })
+        ));
+        assert!(matches!(
+            STANDARD_NO_PAD.decode(b"aGVsbG8*"),
+            Err(DecodeBase64Error::InvalidChar { c: '*', idx: 7 })
+        ));
+    }
+
+    #[test]
+    fn test_alphabet_vs_crate() {
+        // 和 base64 crate 对比, bcrypt 和 crypt 在 base64 crate 中都是不带 padding 的
+        for (engine, config) in [
+            (STANDARD, ::base64::STANDARD),
+            (STANDARD_NO_PAD, ::base64::STANDARD_NO_PAD),
+            (URL_SAFE, ::base64::URL_SAFE),
+            (URL_SAFE_NO_PAD, ::base64::URL_SAFE_NO_PAD),
+            (
+                Base64Engine::new(Alphabet::Bcrypt, Padding::Forbidden),
+                ::base64::BCRYPT,
+            ),
+            (
+                Base64Engine::new(Alphabet::Crypt, Padding::Forbidden),
+                ::base64::CRYPT,
+            ),
+        ] {
+            for n in 0..64 {
+                let data: Vec<u8> = (0..n).map(|i| (i * 37 + n) as u8).collect();
+                let c = engine.encode(&data);
+                assert_eq!(
+                    conv::bytes_to_string(&c),
+                    ::base64::encode_config(&data, config)
+                );
+                if n > 0 {
+                    assert_eq!(engine.decode(&c).unwrap(), data);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_strict() {
+        // `=` 的位置不对
+        for (s, idx) in [
+            ("Y===", 1),
+            ("YQ=A", 2),
+            ("=QAA", 0),
+            ("YWJj====", 4),
+            ("YQ==YQ==", 2),
+        ] {
+            assert!(matches!(
+                decode(s.as_bytes()),
+                Err(DecodeBase64Error::InvalidPadding(i)) if i == idx
+            ));
+        }
+
+        // 多出来的低位不为 0, 非严格模式下可以解码
+        assert_eq!(decode(b"YR==").unwrap(), b"a");
+        assert_eq!(decode(b"YWK=").unwrap(), b"ab");
+        assert!(matches!(
+            decode_strict(b"YR=="),
+            Err(DecodeBase64Error::NonCanonical(1))
+        ));
+        assert!(matches!(
+            decode_strict(b"YWJjYWK="),
+            Err(DecodeBase64Error::NonCanonical(6))
+        ));
+        let engine = URL_SAFE_NO_PAD.with_strict(true);
+        assert!(engine.is_strict());
+        assert!(matches!(
+            engine.decode(b"YR"),
+            Err(DecodeBase64Error::NonCanonical(1))
+        ));
+        assert_eq!(engine.decode(b"YQ").unwrap(), b"a");
+
+        // 合法的编码都能通过
+        for n in 1..32 {
+            let data: Vec<u8> = (0..n).map(|i| (i * 91 + 7) as u8).collect();
+            assert_eq!(decode_strict(&encode(&data)).unwrap(), data);
+            assert_eq!(engine.decode(&engine.encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_wrapped() {
+        let data: Vec<u8> = (0..=255).collect();
+        let body = encode(&data);
+
+        let mime = encode_mime(&data);
+        let lines: Vec<&[u8]> = mime.split(|&c| c == b'\n').collect();
+        assert_eq!(lines.len(), 5);
+        for line in &lines[..4] {
+            assert_eq!(line.len(), MIME_LINE_LEN + 1);
+            assert_eq!(line[MIME_LINE_LEN], b'\r');
+        }
+        assert_eq!(lines[4].len(), body.len() - 4 * MIME_LINE_LEN);
+        assert_eq!(decode_lenient(&mime).unwrap(), data);
+
+        let pem = encode_pem(&data);
+        assert_eq!(pem.len(), body.len() + 5);
+        assert!(pem
+            .split(|&c| c == b'\n')
+            .all(|line| line.len() == PEM_LINE_LEN || line.len() == 24));
+        assert_eq!(decode_lenient(&pem).unwrap(), data);
+
+        // 刚好整行的时候末尾没有换行符
+        assert_eq!(encode_pem(&[0; 48]), [b'A'; 64]);
+        assert!(encode_mime(b"").is_empty());
+
+        assert_eq!(
+            decode_lenient(b" aGVs\r\nbG8g\n\td29y bGQ=\n").unwrap(),
+            b"hello world"
+        );
+        assert!(matches!(
+            decode(b"aGV\nbG8g"),
+            Err(DecodeBase64Error::InvalidChar { c: '\n', idx: 3 })
+        ));
+        assert!(matches!(
+            decode_lenient(b"aGVs\r\n*G8g"),
+            Err(DecodeBase64Error::InvalidChar { c: '*', idx: 6 })
+        ));
+        assert!(matches!(
+            decode_lenient(b"aG\nVs\nY===\n"),
+            Err(DecodeBase64Error::InvalidPadding(7))
+        ));
+        assert!(matches!(
+            STANDARD.with_strict(true).decode_lenient(b"YW\nJj\n YWK="),
+            Err(DecodeBase64Error::NonCanonical(9))
+        ));
+    }
+
+    /// 每次最多读 k 个字节, 用来测试跨 read 的分组
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.1.min(buf.len()).min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn read_all(r: impl Read) -> Result<Vec<u8>, DecodeBase64Error> {
+        let mut r = r;
+        let mut res = Vec::new();
+        match r.read_to_end(&mut res) {
+            Ok(_) => Ok(res),
+            Err(e) => Err(*e.into_inner().unwrap().downcast().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let data: Vec<u8> = (0..10000).map(|i| (i * 7 + i / 13) as u8).collect();
+        for engine in [STANDARD, URL_SAFE_NO_PAD] {
+            let c = engine.encode(&data);
+            for k in [1, 2, 5, 7, 4096, 10000] {
+                let mut w = Base64Writer::with_engine(Vec::new(), engine);
+                for chunk in data.chunks(k) {
+                    w.write_all(chunk).unwrap();
+                }
+                assert_eq!(w.finish().unwrap(), c);
+
+                let r = Base64Reader::with_engine(Trickle(&c, k), engine);
+                assert_eq!(read_all(r).unwrap(), data);
+            }
+        }
+
+        // drop 的时候也会写出最后一组
+        let mut out = Vec::new();
+        {
+            let mut w = Base64Writer::new(&mut out);
+            w.write_all(b"hello world").unwrap();
+            w.flush().unwrap();
+        }
+        assert_eq!(out, b"aGVsbG8gd29ybGQ=");
+        assert!(Base64Writer::new(Vec::new()).finish().unwrap().is_empty());
+
+        // 可以直接读 MIME 格式的
+        let mime = encode_mime(&data);
+        assert_eq!(
+            read_all(Base64Reader::new(Trickle(&mime, 3))).unwrap(),
+            data
+        );
+        assert!(read_all(Base64Reader::new(&b""[..])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stream_error() {
+        let reader = |s: &'static [u8]| Base64Reader::new(Trickle(s, 3));
+        assert!(matches!(
+            read_all(reader(b"aGVs\r\n*G8g")),
+            Err(DecodeBase64Error::InvalidChar { c: '*', idx: 6 })
+        ));
+        assert!(matches!(
+            read_all(reader(b"aGVs\nbG8")),
+            Err(DecodeBase64Error::InvalidLength(7))
+        ));
+        assert!(matches!(
+            read_all(reader(b"YQ==\nYQ==")),
+            Err(DecodeBase64Error::InvalidPadding(2))
+        ));
+        assert!(matches!(
+            read_all(reader(b"aGVs\nY===")),
+            Err(DecodeBase64Error::InvalidPadding(6))
+        ));
+        assert!(matches!(
+            read_all(Base64Reader::with_engine(
+                Trickle(b"YWJj\nYWK=", 1),
+                STANDARD.with_strict(true)
+            )),
+            Err(DecodeBase64Error::NonCanonical(7))
+        ));
+        assert_eq!(read_all(reader(b"YQ==\n\n")).unwrap(), b"a");
+    }
+
+    #[test]
+    fn test_simd() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * i * 31 + i / 7) as u8).collect();
+        for engine in [STANDARD, URL_SAFE_NO_PAD] {
+            let scalar = engine.with_simd(false);
+            for n in (0..200).chain([999, 1000]) {
+                let c = engine.encode(&data[..n]);
+                assert_eq!(c, scalar.encode(&data[..n]));
+                if n > 0 {
+                    assert_eq!(engine.decode(&c).unwrap(), &data[..n]);
+                }
+            }
+
+            // 非法字符在不同位置时, 报错和查表实现一样
+            let c = engine.encode(&data);
+            for (i, bad) in [0, 5, 17, 31, 32, 63, 100, 1000, 1332]
+                .into_iter()
+                .zip(b"*+/=-_\n\x80.".iter().cycle())
+            {
+                let mut c = c.clone();
+                c[i] = *bad;
+                assert_eq!(
+                    engine.decode(&c).map_err(|e| e.to_string()),
+                    scalar.decode(&c).map_err(|e| e.to_string())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_into() {
+        assert_eq!(encoded_len(0, true), 0);
+        assert_eq!(encoded_len(1, true), 4);
+        assert_eq!(encoded_len(1, false), 2);
+        assert_eq!(encoded_len(5, false), 7);
+        assert_eq!(encoded_len(6, true), 8);
+        assert_eq!(decoded_len_estimate(4), 3);
+        assert_eq!(decoded_len_estimate(7), 6);
+
+        let data: Vec<u8> = (0..300u32).map(|i| (i * 13 + 5) as u8).collect();
+        let mut buf = [0; 512];
+        let mut back = [0xaa; 512];
+        for engine in [STANDARD, URL_SAFE_NO_PAD, STANDARD.with_simd(false)] {
+            for n in 0..data.len() {
+                let m = engine.encode_into(&data[..n], &mut buf).unwrap();
+                assert_eq!(m, engine.encoded_len(n));
+                assert_eq!(&buf[..m], engine.encode(&data[..n]));
+                if n == 0 {
+                    continue;
+                }
+
+                // 输出的长度刚好够也可以
+                let k = engine.decode_into(&buf[..m], &mut back[..n]).unwrap();
+                assert_eq!(&back[..k], &data[..n]);
+                assert!(decoded_len_estimate(m) >= n);
+            }
+        }
+
+        assert_eq!(encode_into(b"hello", &mut buf).unwrap(), 8);
+        assert_eq!(decode_into(&buf[..8], &mut back[..5]).unwrap(), 5);
+        assert_eq!(&back[..5], b"hello");
+        assert!(matches!(
+            decode_into(b"aGVsbG8*", &mut back),
+            Err(DecodeBase64Error::InvalidChar { c: '*', idx: 7 })
+        ));
+    }
+
+    #[test]
+    fn test_into_too_small() {
+        assert!(matches!(
+            encode_into(b"hello", &mut [0; 7]),
+            Err(ConvError::OutputTooSmall { len: 7, needed: 8 })
+        ));
+        assert!(matches!(
+            decode_into(b"aGVsbG8=", &mut [0; 4]),
+            Err(DecodeBase64Error::OutputTooSmall { len: 4, needed: 5 })
+        ));
+    }
 }
@@ -2,6 +2,27 @@ pub const INVALID_VALUE: u8 = 0xFF;
 
 pub const STANDARD_ENCODE: &[u8; 64] =
     b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+pub const URL_SAFE_ENCODE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+pub const BCRYPT_ENCODE: &[u8; 64] =
+    b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+pub const CRYPT_ENCODE: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+pub const URL_SAFE_DECODE: &[u8; 256] = &make_decode_table(URL_SAFE_ENCODE);
+pub const BCRYPT_DECODE: &[u8; 256] = &make_decode_table(BCRYPT_ENCODE);
+pub const CRYPT_DECODE: &[u8; 256] = &make_decode_table(CRYPT_ENCODE);
+
+/// 编译期由字母表生成反查表
+const fn make_decode_table(alphabet: &[u8; 64]) -> [u8; 256] {
+    let mut table = [INVALID_VALUE; 256];
+    let mut i = 0;
+    while i < 64 {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
 
 // copy from base64 crate
 pub const STANDARD_DECODE: &[u8; 256] = &[
@@ -66,7 +87,7 @@ pub const STANDARD_DECODE: &[u8; 256] = &[
     INVALID_VALUE, // input 58 (0x3A)
     INVALID_VALUE, // input 59 (0x3B)
     INVALID_VALUE, // input 60 (0x3C)
-    0,             // input 61 (0x3D)
+    INVALID_VALUE, // input 61 (0x3D)
     INVALID_VALUE, // input 62 (0x3E)
     INVALID_VALUE, // input 63 (0x3F)
     INVALID_VALUE, // input 64 (0x40)
@@ -0,0 +1,310 @@
+//! SSSE3 / AVX2 加速的 base64, 算法来自 Wojciech Muła, Daniel Lemire:
+//! "Faster Base64 Encoding and Decoding Using AVX2 Instructions"
+//!
+//! 只处理中间完整的分组, 剩下的部分 (以及非法字符) 交给 base64.rs 的查表实现,
+//! 所以这里不需要关心 padding 和报错. 只支持 standard 和 URL-safe 两种字母表,
+//! bcrypt 和 crypt 的字母表顺序不一样, 直接返回 (0, 0).
+
+use crate::base64::Alphabet;
+
+/// 返回 (读入的字节数, 写出的字节数), 读入的一定是 3 的倍数
+pub(crate) fn encode(msg: &[u8], res: &mut [u8], alphabet: Alphabet) -> (usize, usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let specials = match alphabet {
+            Alphabet::Standard => (b'+', b'/'),
+            Alphabet::UrlSafe => (b'-', b'_'),
+            _ => return (0, 0),
+        };
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { x86::encode_avx2(msg, res, specials) };
+        }
+        if is_x86_feature_detected!("ssse3") {
+            return unsafe { x86::encode_ssse3(msg, res, specials) };
+        }
+    }
+    let _ = (msg, res, alphabet);
+    (0, 0)
+}
+
+/// 返回 (读入的字符数, 写出的字节数), 读入的一定是 4 的倍数, 遇到非法字符就停下
+pub(crate) fn decode(msg: &[u8], res: &mut [u8], alphabet: Alphabet) -> (usize, usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let url_safe = match alphabet {
+            Alphabet::Standard => false,
+            Alphabet::UrlSafe => true,
+            _ => return (0, 0),
+        };
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { x86::decode_avx2(msg, res, url_safe) };
+        }
+        if is_x86_feature_detected!("ssse3") {
+            return unsafe { x86::decode_ssse3(msg, res, url_safe) };
+        }
+    }
+    let _ = (msg, res, alphabet);
+    (0, 0)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    // 6 bit 的值 -> 字符的偏移量, 下标由 enc_translate 算出来
+    // 0..=25: 'A', 26..=51: 'a' - 26, 52..=61: '0' - 52, 62, 63: 两个特殊字符
+    macro_rules! enc_lut {
+        ($c62:expr, $c63:expr) => {{
+            let d = (b'0' as i8).wrapping_sub(52);
+            _mm_setr_epi8(
+                (b'a' - 26) as i8,
+                d,
+                d,
+                d,
+                d,
+                d,
+                d,
+                d,
+                d,
+                d,
+                d,
+                ($c62 as i8).wrapping_sub(62),
+                ($c63 as i8).wrapping_sub(63),
+                b'A' as i8,
+                0,
+                0,
+            )
+        }};
+    }
+
+    /// 12 个字节 (每组 3 个) 分到 16 个 u8 中, 每个 u8 是 6 bit 的值
+    #[inline]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn enc_reshuffle(v: __m128i) -> __m128i {
+        let v = _mm_shuffle_epi8(
+            v,
+            _mm_set_epi8(10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1),
+        );
+        let t0 = _mm_and_si128(v, _mm_set1_epi32(0x0fc0fc00));
+        let t1 = _mm_mulhi_epu16(t0, _mm_set1_epi32(0x04000040));
+        let t2 = _mm_and_si128(v, _mm_set1_epi32(0x003f03f0));
+        let t3 = _mm_mullo_epi16(t2, _mm_set1_epi32(0x01000010));
+        _mm_or_si128(t1, t3)
+    }
+
+    #[inline]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn enc_translate(v: __m128i, lut: __m128i) -> __m128i {
+        let idx = _mm_subs_epu8(v, _mm_set1_epi8(51));
+        let less = _mm_cmpgt_epi8(_mm_set1_epi8(26), v);
+        let idx = _mm_or_si128(idx, _mm_and_si128(less, _mm_set1_epi8(13)));
+        _mm_add_epi8(v, _mm_shuffle_epi8(lut, idx))
+    }
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn encode_ssse3(
+        msg: &[u8],
+        res: &mut [u8],
+        specials: (u8, u8),
+    ) -> (usize, usize) {
+        let lut = enc_lut!(specials.0, specials.1);
+        let mut i = 0;
+        let mut o = 0;
+        // 每次读 16 个字节, 只用前 12 个
+        while i + 16 <= msg.len() && o + 16 <= res.len() {
+            let v = _mm_loadu_si128(msg.as_ptr().add(i) as *const __m128i);
+            let v = enc_translate(enc_reshuffle(v), lut);
+            _mm_storeu_si128(res.as_mut_ptr().add(o) as *mut __m128i, v);
+            i += 12;
+            o += 16;
+        }
+        (i, o)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn encode_avx2(
+        msg: &[u8],
+        res: &mut [u8],
+        specials: (u8, u8),
+    ) -> (usize, usize) {
+        let lut = _mm256_broadcastsi128_si256(enc_lut!(specials.0, specials.1));
+        let shuf = _mm256_set_epi8(
+            10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1, //
+            10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1,
+        );
+        let mut i = 0;
+        let mut o = 0;
+        // 两个 128 位的 lane 分别读 [i, i + 16) 和 [i + 12, i + 28)
+        while i + 28 <= msg.len() && o + 32 <= res.len() {
+            let lo = _mm_loadu_si128(msg.as_ptr().add(i) as *const __m128i);
+            let hi = _mm_loadu_si128(msg.as_ptr().add(i + 12) as *const __m128i);
+            let v = _mm256_shuffle_epi8(_mm256_set_m128i(hi, lo), shuf);
+
+            let t0 = _mm256_and_si256(v, _mm256_set1_epi32(0x0fc0fc00));
+            let t1 = _mm256_mulhi_epu16(t0, _mm256_set1_epi32(0x04000040));
+            let t2 = _mm256_and_si256(v, _mm256_set1_epi32(0x003f03f0));
+            let t3 = _mm256_mullo_epi16(t2, _mm256_set1_epi32(0x01000010));
+            let v = _mm256_or_si256(t1, t3);
+
+            let idx = _mm256_subs_epu8(v, _mm256_set1_epi8(51));
+            let less = _mm256_cmpgt_epi8(_mm256_set1_epi8(26), v);
+            let idx = _mm256_or_si256(idx, _mm256_and_si256(less, _mm256_set1_epi8(13)));
+            let v = _mm256_add_epi8(v, _mm256_shuffle_epi8(lut, idx));
+
+            _mm256_storeu_si256(res.as_mut_ptr().add(o) as *mut __m256i, v);
+            i += 24;
+            o += 32;
+        }
+        let (di, d_o) = encode_ssse3(&msg[i..], &mut res[o..], specials);
+        (i + di, o + d_o)
+    }
+
+    // 按高 4 位和低 4 位查表, 两个结果 and 之后不为 0 就是非法字符
+    macro_rules! dec_luts {
+        () => {
+            (
+                _mm_setr_epi8(
+                    0x15, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, //
+                    0x11, 0x11, 0x13, 0x1a, 0x1b, 0x1b, 0x1b, 0x1a,
+                ),
+                _mm_setr_epi8(
+                    0x10, 0x10, 0x01, 0x02, 0x04, 0x08, 0x04, 0x08, //
+                    0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10,
+                ),
+                // 字符 -> 6 bit 的值的偏移量, '/' 单独处理
+                _mm_setr_epi8(0, 16, 19, 4, -65, -65, -71, -71, 0, 0, 0, 0, 0, 0, 0, 0),
+            )
+        };
+    }
+
+    /// URL-safe 的先把 '-' '_' 换成 '+' '/', 原来的 '+' '/' 是非法的, 返回 None
+    #[inline]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn url_safe_to_standard(v: __m128i) -> Option<__m128i> {
+        let bad = _mm_or_si128(
+            _mm_cmpeq_epi8(v, _mm_set1_epi8(b'+' as i8)),
+            _mm_cmpeq_epi8(v, _mm_set1_epi8(b'/' as i8)),
+        );
+        if _mm_movemask_epi8(bad) != 0 {
+            return None;
+        }
+        let minus = _mm_cmpeq_epi8(v, _mm_set1_epi8(b'-' as i8));
+        let underscore = _mm_cmpeq_epi8(v, _mm_set1_epi8(b'_' as i8));
+        let v = _mm_xor_si128(v, _mm_and_si128(minus, _mm_set1_epi8((b'-' ^ b'+') as i8)));
+        Some(_mm_xor_si128(
+            v,
+            _mm_and_si128(underscore, _mm_set1_epi8((b'_' ^ b'/') as i8)),
+        ))
+    }
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn decode_ssse3(
+        msg: &[u8],
+        res: &mut [u8],
+        url_safe: bool,
+    ) -> (usize, usize) {
+        let (lut_lo, lut_hi, lut_roll) = dec_luts!();
+        let mask_2f = _mm_set1_epi8(0x2f);
+        let mut i = 0;
+        let mut o = 0;
+        // 每次读 16 个字符, 写 16 个字节, 只有前 12 个有用
+        while i + 16 <= msg.len() && o + 16 <= res.len() {
+            let mut v = _mm_loadu_si128(msg.as_ptr().add(i) as *const __m128i);
+            if url_safe {
+                match url_safe_to_standard(v) {
+                    Some(t) => v = t,
+                    None => break,
+                }
+            }
+
+            let hi_nibbles = _mm_and_si128(_mm_srli_epi32(v, 4), mask_2f);
+            let lo_nibbles = _mm_and_si128(v, mask_2f);
+            let hi = _mm_shuffle_epi8(lut_hi, hi_nibbles);
+            let lo = _mm_shuffle_epi8(lut_lo, lo_nibbles);
+            let invalid = _mm_cmpgt_epi8(_mm_and_si128(lo, hi), _mm_setzero_si128());
+            if _mm_movemask_epi8(invalid) != 0 {
+                break;
+            }
+
+            let eq_2f = _mm_cmpeq_epi8(v, mask_2f);
+            let roll = _mm_shuffle_epi8(lut_roll, _mm_add_epi8(eq_2f, hi_nibbles));
+            let v = _mm_add_epi8(v, roll);
+
+            // 4 个 6 bit 合成 3 个字节
+            let v = _mm_maddubs_epi16(v, _mm_set1_epi32(0x01400140));
+            let v = _mm_madd_epi16(v, _mm_set1_epi32(0x00011000));
+            let v = _mm_shuffle_epi8(
+                v,
+                _mm_setr_epi8(2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1),
+            );
+            _mm_storeu_si128(res.as_mut_ptr().add(o) as *mut __m128i, v);
+            i += 16;
+            o += 12;
+        }
+        (i, o)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn decode_avx2(msg: &[u8], res: &mut [u8], url_safe: bool) -> (usize, usize) {
+        let (lut_lo, lut_hi, lut_roll) = dec_luts!();
+        let lut_lo = _mm256_broadcastsi128_si256(lut_lo);
+        let lut_hi = _mm256_broadcastsi128_si256(lut_hi);
+        let lut_roll = _mm256_broadcastsi128_si256(lut_roll);
+        let mask_2f = _mm256_set1_epi8(0x2f);
+        let mut i = 0;
+        let mut o = 0;
+        // 每次读 32 个字符, 两个 lane 各写 16 个字节, 第二次写会覆盖第一次多写的 4 个
+        while i + 32 <= msg.len() && o + 28 <= res.len() {
+            let mut v = _mm256_loadu_si256(msg.as_ptr().add(i) as *const __m256i);
+            if url_safe {
+                let bad = _mm256_or_si256(
+                    _mm256_cmpeq_epi8(v, _mm256_set1_epi8(b'+' as i8)),
+                    _mm256_cmpeq_epi8(v, _mm256_set1_epi8(b'/' as i8)),
+                );
+                if _mm256_movemask_epi8(bad) != 0 {
+                    break;
+                }
+                let minus = _mm256_cmpeq_epi8(v, _mm256_set1_epi8(b'-' as i8));
+                let underscore = _mm256_cmpeq_epi8(v, _mm256_set1_epi8(b'_' as i8));
+                v = _mm256_xor_si256(
+                    v,
+                    _mm256_and_si256(minus, _mm256_set1_epi8((b'-' ^ b'+') as i8)),
+                );
+                v = _mm256_xor_si256(
+                    v,
+                    _mm256_and_si256(underscore, _mm256_set1_epi8((b'_' ^ b'/') as i8)),
+                );
+            }
+
+            let hi_nibbles = _mm256_and_si256(_mm256_srli_epi32(v, 4), mask_2f);
+            let lo_nibbles = _mm256_and_si256(v, mask_2f);
+            let hi = _mm256_shuffle_epi8(lut_hi, hi_nibbles);
+            let lo = _mm256_shuffle_epi8(lut_lo, lo_nibbles);
+            let invalid = _mm256_cmpgt_epi8(_mm256_and_si256(lo, hi), _mm256_setzero_si256());
+            if _mm256_movemask_epi8(invalid) != 0 {
+                break;
+            }
+
+            let eq_2f = _mm256_cmpeq_epi8(v, mask_2f);
+            let roll = _mm256_shuffle_epi8(lut_roll, _mm256_add_epi8(eq_2f, hi_nibbles));
+            let v = _mm256_add_epi8(v, roll);
+
+            let v = _mm256_maddubs_epi16(v, _mm256_set1_epi32(0x01400140));
+            let v = _mm256_madd_epi16(v, _mm256_set1_epi32(0x00011000));
+            let v = _mm256_shuffle_epi8(
+                v,
+                _mm256_setr_epi8(
+                    2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1, //
+                    2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1,
+                ),
+            );
+            let out = res.as_mut_ptr().add(o);
+            _mm_storeu_si128(out as *mut __m128i, _mm256_castsi256_si128(v));
+            _mm_storeu_si128(out.add(12) as *mut __m128i, _mm256_extracti128_si256(v, 1));
+            i += 32;
+            o += 24;
+        }
+        let (di, d_o) = decode_ssse3(&msg[i..], &mut res[o..], url_safe);
+        (i + di, o + d_o)
+    }
+}
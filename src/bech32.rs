@@ -0,0 +1,346 @@
+//! Bech32 (BIP-173) 和 Bech32m (BIP-350): hrp + '1' + 5 bit 的数据 + 6 个字符的 BCH 校验和
+//! SegWit v0 地址用 Bech32, v1 (Taproot) 及以上用 Bech32m
+
+use crate::bech32_const::{
+    BECH32M_CONST, BECH32_CONST, CHARSET, CHARSET_DECODE, GENERATOR, INVALID_VALUE,
+};
+use crate::error::Bech32Error;
+
+const MAX_LEN: usize = 90;
+const CHECKSUM_LEN: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    #[inline]
+    fn constant(self) -> u32 {
+        match self {
+            Self::Bech32 => BECH32_CONST,
+            Self::Bech32m => BECH32M_CONST,
+        }
+    }
+}
+
+/// data 中每个元素是 5 bit 的值, 输出全是小写
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, Bech32Error> {
+    let hrp = hrp.as_bytes();
+    if hrp.is_empty() || hrp.iter().any(|&c| !(33..=126).contains(&c)) {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    let n = hrp.len() + 1 + data.len() + CHECKSUM_LEN;
+    if n > MAX_LEN {
+        return Err(Bech32Error::TooLong(n));
+    }
+    if let Some(&v) = data.iter().find(|&&v| v >= 32) {
+        return Err(Bech32Error::InvalidValue(v));
+    }
+
+    let hrp = hrp.to_ascii_lowercase();
+    let checksum = create_checksum(&hrp, data, variant);
+    let mut res = String::with_capacity(n);
+    res.extend(hrp.iter().map(|&c| c as char));
+    res.push('1');
+    res.extend(
+        data.iter()
+            .chain(&checksum)
+            .map(|&v| CHARSET[v as usize] as char),
+    );
+    Ok(res)
+}
+
+/// 返回 (hrp, 5 bit 的数据, 校验和的类型), hrp 转成了小写
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Bech32Error> {
+    let s = s.as_bytes();
+    if s.len() > MAX_LEN {
+        return Err(Bech32Error::TooLong(s.len()));
+    }
+    if let Some(idx) = s.iter().position(|c| !(33..=126).contains(c)) {
+        return Err(Bech32Error::InvalidChar {
+            c: s[idx] as char,
+            idx,
+        });
+    }
+    if s.iter().any(u8::is_ascii_lowercase) && s.iter().any(u8::is_ascii_uppercase) {
+        return Err(Bech32Error::MixedCase);
+    }
+
+    // hrp 里也可以有 '1', 以最后一个为准
+    let sep = s
+        .iter()
+        .rposition(|&c| c == b'1')
+        .ok_or(Bech32Error::MissingSeparator)?;
+    if sep == 0 {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    let n_data = s.len() - sep - 1;
+    if n_data < CHECKSUM_LEN {
+        return Err(Bech32Error::TooShort(n_data));
+    }
+
+    let hrp = s[..sep].to_ascii_lowercase();
+    let mut data = Vec::with_capacity(n_data);
+    for (idx, &c) in s.iter().enumerate().skip(sep + 1) {
+        match CHARSET_DECODE[c as usize] {
+            INVALID_VALUE => return Err(Bech32Error::InvalidChar { c: c as char, idx }),
+            v => data.push(v),
+        }
+    }
+
+    let variant = match polymod(&hrp, &data) {
+        BECH32_CONST => Variant::Bech32,
+        BECH32M_CONST => Variant::Bech32m,
+        _ => return Err(Bech32Error::InvalidChecksum),
+    };
+    data.truncate(n_data - CHECKSUM_LEN);
+    // hrp 只有 ascii
+    Ok((String::from_utf8(hrp).unwrap(), data, variant))
+}
+
+/// 按 bit 重新分组, 比如 8 bit -> 5 bit (pad = true, 不足的补 0)
+/// 或者 5 bit -> 8 bit (pad = false, 多出来的不能超过 from 位, 而且必须是 0)
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Bech32Error> {
+    let max_v = (1u32 << to) - 1;
+    let max_acc = (1u32 << (from + to - 1)) - 1;
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut res = Vec::with_capacity((data.len() * from as usize).div_ceil(to as usize));
+    for &v in data {
+        if (v as u32) >> from != 0 {
+            return Err(Bech32Error::InvalidValue(v));
+        }
+        acc = (acc << from | v as u32) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            res.push((acc >> bits & max_v) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            res.push((acc << (to - bits) & max_v) as u8);
+        }
+    } else if bits >= from || acc << (to - bits) & max_v != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(res)
+}
+
+/// SegWit 地址, 比如 hrp = "bc" (主网) 或 "tb" (测试网)
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String, Bech32Error> {
+    check_witness(version, program.len())?;
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    let variant = match version {
+        0 => Variant::Bech32,
+        _ => Variant::Bech32m,
+    };
+    encode(hrp, &data, variant)
+}
+
+/// 返回 (witness version, witness program)
+pub fn decode_segwit(hrp: &str, addr: &str) -> Result<(u8, Vec<u8>), Bech32Error> {
+    let (found, data, variant) = decode(addr)?;
+    if found != hrp {
+        return Err(Bech32Error::UnexpectedHrp(found));
+    }
+    let (&version, rest) = data
+        .split_first()
+        .ok_or(Bech32Error::InvalidProgramLength(0))?;
+    let program = convert_bits(rest, 5, 8, false)?;
+    check_witness(version, program.len())?;
+    match (version, variant) {
+        (0, Variant::Bech32) | (1.., Variant::Bech32m) => Ok((version, program)),
+        _ => Err(Bech32Error::InvalidVariant),
+    }
+}
+
+/// version 0 ~ 16, program 2 ~ 40 个字节, v0 只能是 20 (P2WPKH) 或 32 (P2WSH)
+fn check_witness(version: u8, len: usize) -> Result<(), Bech32Error> {
+    if version > 16 {
+        return Err(Bech32Error::InvalidWitnessVersion(version));
+    }
+    if !(2..=40).contains(&len) || (version == 0 && len != 20 && len != 32) {
+        return Err(Bech32Error::InvalidProgramLength(len));
+    }
+    Ok(())
+}
+
+fn polymod(hrp: &[u8], data: &[u8]) -> u32 {
+    // hrp 展开成: 每个字符的高 3 位, 0, 每个字符的低 5 位
+    let values = hrp
+        .iter()
+        .map(|&c| c >> 5)
+        .chain([0])
+        .chain(hrp.iter().map(|&c| c & 0x1f))
+        .chain(data.iter().copied());
+
+    let mut chk = 1u32;
+    for v in values {
+        let b = chk >> 25;
+        chk = (chk & 0x1ffffff) << 5 ^ v as u32;
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn create_checksum(hrp: &[u8], data: &[u8], variant: Variant) -> [u8; CHECKSUM_LEN] {
+    let mut values = data.to_vec();
+    values.extend([0; CHECKSUM_LEN]);
+    let m = polymod(hrp, &values) ^ variant.constant();
+    let mut res = [0; CHECKSUM_LEN];
+    for (i, v) in res.iter_mut().enumerate() {
+        *v = (m >> (5 * (5 - i)) & 0x1f) as u8;
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::hex_to_bytes;
+
+    #[test]
+    fn test_bech32() {
+        // BIP-173 和 BIP-350 的测试数据
+        for (s, variant) in [
+            ("A12UEL5L", Variant::Bech32),
+            ("a12uel5l", Variant::Bech32),
+            ("an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs", Variant::Bech32),
+            ("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw", Variant::Bech32),
+            ("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w", Variant::Bech32),
+            ("?1ezyfcl", Variant::Bech32),
+            ("A1LQFN3A", Variant::Bech32m),
+            ("a1lqfn3a", Variant::Bech32m),
+            ("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", Variant::Bech32m),
+            ("split1checkupstagehandshakeupstreamerranterredcaperredlc445v", Variant::Bech32m),
+            ("?1v759aa", Variant::Bech32m),
+        ] {
+            let (hrp, data, found) = decode(s).unwrap();
+            assert_eq!(found, variant);
+            assert_eq!(encode(&hrp, &data, variant).unwrap(), s.to_lowercase());
+        }
+
+        let (_, data, _) = decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").unwrap();
+        assert_eq!(data, (0..32).collect::<Vec<u8>>());
+
+        assert!(matches!(
+            decode("\x201nwldj5"),
+            Err(Bech32Error::InvalidChar { c: ' ', idx: 0 })
+        ));
+        assert!(matches!(
+            decode("pzry9x0s0muk"),
+            Err(Bech32Error::MissingSeparator)
+        ));
+        assert!(matches!(
+            decode("1pzry9x0s0muk"),
+            Err(Bech32Error::InvalidHrp)
+        ));
+        assert!(matches!(
+            decode("x1b4n0q5v"),
+            Err(Bech32Error::InvalidChar { c: 'b', idx: 2 })
+        ));
+        assert!(matches!(decode("li1dgmt3"), Err(Bech32Error::TooShort(5))));
+        assert!(matches!(
+            decode("A1G7SGD8"),
+            Err(Bech32Error::InvalidChecksum)
+        ));
+        assert!(matches!(decode("A12uEL5L"), Err(Bech32Error::MixedCase)));
+        assert!(matches!(
+            decode("an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx"),
+            Err(Bech32Error::TooLong(91))
+        ));
+        assert!(matches!(
+            encode("a", &[32], Variant::Bech32),
+            Err(Bech32Error::InvalidValue(32))
+        ));
+    }
+
+    #[test]
+    fn test_convert_bits() {
+        let data = hex_to_bytes("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let five = convert_bits(&data, 8, 5, true).unwrap();
+        assert_eq!(five.len(), 32);
+        assert_eq!(convert_bits(&five, 5, 8, false).unwrap(), data);
+
+        // 多出来的位不为 0, 或者多出了一整组
+        assert!(matches!(
+            convert_bits(&[0x1f], 5, 8, false),
+            Err(Bech32Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            convert_bits(&[0, 0, 0, 0, 0, 0], 5, 8, false),
+            Err(Bech32Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            convert_bits(&[0x20], 5, 8, false),
+            Err(Bech32Error::InvalidValue(0x20))
+        ));
+    }
+
+    #[test]
+    fn test_segwit() {
+        for (addr, hrp, version, program) in [
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "bc",
+                0,
+                "751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                "tb",
+                0,
+                "1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "bc",
+                1,
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ] {
+            let program = hex_to_bytes(program).unwrap();
+            assert_eq!(
+                decode_segwit(hrp, addr).unwrap(),
+                (version, program.clone())
+            );
+            assert_eq!(
+                encode_segwit(hrp, version, &program).unwrap(),
+                addr.to_lowercase()
+            );
+        }
+
+        // v0 用了 Bech32m, v1 用了 Bech32
+        assert!(matches!(
+            decode_segwit("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh"),
+            Err(Bech32Error::InvalidVariant)
+        ));
+        assert!(matches!(
+            decode_segwit(
+                "bc",
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+            ),
+            Err(Bech32Error::InvalidVariant)
+        ));
+        assert!(matches!(
+            decode_segwit("tb", "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            Err(Bech32Error::UnexpectedHrp(_))
+        ));
+        assert!(matches!(
+            encode_segwit("bc", 0, &[0; 21]),
+            Err(Bech32Error::InvalidProgramLength(21))
+        ));
+        assert!(matches!(
+            encode_segwit("bc", 17, &[0; 32]),
+            Err(Bech32Error::InvalidWitnessVersion(17))
+        ));
+    }
+}
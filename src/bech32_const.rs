@@ -0,0 +1,24 @@
+pub const INVALID_VALUE: u8 = 0xFF;
+
+pub const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// 大小写都可以, 混用的情况在解码时单独检查
+pub const CHARSET_DECODE: &[u8; 256] = &make_decode_table(CHARSET);
+
+/// BCH 码的生成多项式
+pub const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+/// 校验和的常数, BIP-350 把 1 换成了 0x2bc830a3
+pub const BECH32_CONST: u32 = 1;
+pub const BECH32M_CONST: u32 = 0x2bc830a3;
+
+const fn make_decode_table(charset: &[u8; 32]) -> [u8; 256] {
+    let mut table = [INVALID_VALUE; 256];
+    let mut i = 0;
+    while i < 32 {
+        table[charset[i] as usize] = i as u8;
+        table[charset[i].to_ascii_uppercase() as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
@@ -1,12 +1,119 @@
-use crate::error::DecodeHexError;
+use crate::error::{ConvError, DecodeHexError};
+use crate::hex_const::{DECODE, INVALID_VALUE, LOWER_ENCODE, UPPER_ENCODE};
+use crate::hex_simd;
+use num_bigint::BigUint;
+use std::fmt;
 
-pub fn toggle_endian<T>(lst: Vec<T>) -> Vec<u8>
+/// 每一段 hex 分别解码后倒序 (大小端互换), 再拼起来
+pub fn toggle_endian<T>(lst: Vec<T>) -> Result<Vec<u8>, DecodeHexError>
 where
     T: AsRef<[u8]>,
 {
-    lst.into_iter()
-        .flat_map(|x| hex_to_bytes(x).unwrap().into_iter().rev())
-        .collect()
+    let mut res = Vec::new();
+    for x in lst {
+        let bytes = hex_to_bytes(x)?;
+        res.extend(bytes.into_iter().rev());
+    }
+    Ok(res)
+}
+
+/// 定长的无符号整数, 从切片读的时候长度必须刚好是 `SIZE`
+pub trait FixedInt: Copy {
+    const SIZE: usize;
+
+    fn read_be(bytes: &[u8]) -> Result<Self, ConvError>;
+    fn read_le(bytes: &[u8]) -> Result<Self, ConvError>;
+    /// out 的长度不是 `SIZE` 时 panic
+    fn write_be(self, out: &mut [u8]);
+    fn write_le(self, out: &mut [u8]);
+}
+
+macro_rules! impl_fixed_int {
+    ($($t:ty),*) => {
+        $(
+            impl FixedInt for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn read_be(bytes: &[u8]) -> Result<Self, ConvError> {
+                    Ok(<$t>::from_be_bytes(fixed(bytes)?))
+                }
+
+                fn read_le(bytes: &[u8]) -> Result<Self, ConvError> {
+                    Ok(<$t>::from_le_bytes(fixed(bytes)?))
+                }
+
+                fn write_be(self, out: &mut [u8]) {
+                    out.copy_from_slice(&self.to_be_bytes());
+                }
+
+                fn write_le(self, out: &mut [u8]) {
+                    out.copy_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_int!(u16, u32, u64, u128);
+
+#[inline]
+fn fixed<const M: usize>(bytes: &[u8]) -> Result<[u8; M], ConvError> {
+    bytes.try_into().map_err(|_| ConvError::InvalidLength {
+        expected: M,
+        found: bytes.len(),
+    })
+}
+
+/// 大整数编码成 len 字节的 big endian (I2OSP), 左侧补零, 放不下时返回错误
+pub fn biguint_to_be(x: &BigUint, len: usize) -> Result<Vec<u8>, ConvError> {
+    // to_bytes_be(0) 会返回 [0], 交给 pad_left 去掉
+    pad_left(&x.to_bytes_be(), len)
+}
+
+/// 和 `biguint_to_be` 一样, 只是 little endian, 右侧补零
+pub fn biguint_to_le(x: &BigUint, len: usize) -> Result<Vec<u8>, ConvError> {
+    let mut res = biguint_to_be(x, len)?;
+    res.reverse();
+    Ok(res)
+}
+
+/// OS2IP, 空切片是 0
+pub fn biguint_from_be(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+pub fn biguint_from_le(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_le(bytes)
+}
+
+/// 左侧补零到 len 字节; 太长的话可以去掉开头多余的零 (比如 DER 整数的符号位),
+/// 非零部分放不下时返回错误
+pub fn pad_left(bytes: &[u8], len: usize) -> Result<Vec<u8>, ConvError> {
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    let bytes = &bytes[skip.min(bytes.len().saturating_sub(len))..];
+    if bytes.len() > len {
+        return Err(ConvError::TooLarge {
+            len: bytes.len(),
+            max: len,
+        });
+    }
+    let mut res = vec![0; len];
+    res[len - bytes.len()..].copy_from_slice(bytes);
+    Ok(res)
+}
+
+/// 每 word 个字节分别倒序 (原地), MTProto 之类的协议里整数是 little endian 存的
+pub fn swap_words(bytes: &mut [u8], word: usize) -> Result<(), ConvError> {
+    if word == 0 || bytes.len() % word != 0 {
+        return Err(ConvError::NotMultiple {
+            len: bytes.len(),
+            word,
+        });
+    }
+    for w in bytes.chunks_exact_mut(word) {
+        w.reverse();
+    }
+    Ok(())
 }
 
 pub fn str_to_bytes(s: &str) -> Vec<u8> {
@@ -18,42 +125,316 @@ pub fn bytes_to_string(bytes: &[u8]) -> String {
 }
 
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
-    bytes.iter().map(|c| format!("{:02x}", c)).collect()
+    bytes_to_hex_lower(bytes)
 }
 
 pub fn bytes_to_hex_lower(bytes: &[u8]) -> String {
-    bytes.iter().map(|c| format!("{:02x}", c)).collect()
+    encode_hex(bytes, LOWER_ENCODE)
 }
 
 pub fn bytes_to_hex_upper(bytes: &[u8]) -> String {
-    bytes.iter().map(|c| format!("{:02X}", c)).collect()
+    encode_hex(bytes, UPPER_ENCODE)
 }
 
 pub fn hex_to_bytes<T>(hex_str: T) -> Result<Vec<u8>, DecodeHexError>
 where
     T: AsRef<[u8]>,
 {
-    match hex_str.as_ref().len() {
-        n if n & 1 == 1 => Err(DecodeHexError::OddLength(n)),
-        _ => hex_str
-            .as_ref()
-            .chunks(2)
-            .enumerate()
-            .map(|(i, pair)| Ok(hc2u8(pair[0], i << 1)? << 4 | hc2u8(pair[1], (i << 1) + 1)?))
-            .collect(),
+    let hex = hex_str.as_ref();
+    let mut res = vec![0; hex.len() / 2];
+    hex_decode_into(hex, &mut res)?;
+    Ok(res)
+}
+
+/// 小写 hex 编码到 out 中, 返回写入的长度 (bytes.len() * 2)
+pub fn hex_encode_into(bytes: &[u8], out: &mut [u8]) -> Result<usize, ConvError> {
+    check_hex_out(bytes, out)?;
+    Ok(encode_hex_into(bytes, out, LOWER_ENCODE))
+}
+
+pub fn hex_encode_upper_into(bytes: &[u8], out: &mut [u8]) -> Result<usize, ConvError> {
+    check_hex_out(bytes, out)?;
+    Ok(encode_hex_into(bytes, out, UPPER_ENCODE))
+}
+
+fn check_hex_out(bytes: &[u8], out: &[u8]) -> Result<(), ConvError> {
+    let needed = bytes.len() * 2;
+    if out.len() < needed {
+        return Err(ConvError::OutputTooSmall {
+            len: out.len(),
+            needed,
+        });
+    }
+    Ok(())
+}
+
+/// 解码到 out 中, 返回写入的长度 (hex.len() / 2), out 不够长时返回 OutputTooSmall
+pub fn hex_decode_into(hex: &[u8], out: &mut [u8]) -> Result<usize, DecodeHexError> {
+    let n = hex.len();
+    if n & 1 == 1 {
+        return Err(DecodeHexError::OddLength(n));
+    }
+    let m = n / 2;
+    if out.len() < m {
+        return Err(DecodeHexError::OutputTooSmall {
+            len: out.len(),
+            needed: m,
+        });
+    }
+
+    let (mut i, mut o) = hex_simd::decode(hex, &mut out[..m]);
+    while i < n {
+        out[o] = hc2u8(hex[i], i)? << 4 | hc2u8(hex[i + 1], i + 1)?;
+        i += 2;
+        o += 1;
+    }
+    Ok(m)
+}
+
+fn encode_hex(bytes: &[u8], table: &[u8; 16]) -> String {
+    let mut res = vec![0; bytes.len() * 2];
+    encode_hex_into(bytes, &mut res, table);
+    // hex 一定是 ascii
+    String::from_utf8(res).unwrap()
+}
+
+/// out 的长度由调用方保证
+fn encode_hex_into(bytes: &[u8], out: &mut [u8], table: &[u8; 16]) -> usize {
+    let m = bytes.len() * 2;
+
+    let (i, mut o) = hex_simd::encode(bytes, &mut out[..m], table);
+    for &b in &bytes[i..] {
+        out[o] = table[(b >> 4) as usize];
+        out[o + 1] = table[(b & 0x0f) as usize];
+        o += 2;
     }
+    m
 }
 
+/// 宽松的 hex 解析, 直接粘贴 RFC 或者 OpenSSL 输出的测试数据用:
+/// 可以有 `0x` 前缀, 空白和 `:`/`-` 分隔符, 大小写混用;
+/// 分隔符只能出现在两个字节之间, 出错时的位置是原始输入中的位置
+#[derive(Clone, Copy, Debug)]
+pub struct HexParser {
+    prefix: bool,
+    whitespace: bool,
+    separators: &'static [u8],
+}
+
+impl HexParser {
+    /// 和 `hex_to_bytes` 一样, 只接受 hex 字符
+    pub const STRICT: Self = Self::new()
+        .with_prefix(false)
+        .with_whitespace(false)
+        .with_separators(b"");
+    pub const LENIENT: Self = Self::new();
+
+    /// 默认什么都接受
+    pub const fn new() -> Self {
+        Self {
+            prefix: true,
+            whitespace: true,
+            separators: b":-",
+        }
+    }
+
+    /// 每一组 (开头或者分隔符之后) 可以有 `0x`/`0X` 前缀
+    pub const fn with_prefix(mut self, prefix: bool) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    pub const fn with_whitespace(mut self, whitespace: bool) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    pub const fn with_separators(mut self, separators: &'static [u8]) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    pub fn parse<T>(&self, hex_str: T) -> Result<Vec<u8>, DecodeHexError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let hex = hex_str.as_ref();
+        let mut res = Vec::with_capacity(hex.len() / 2);
+        let mut hi = None; // 已经读到的高 4 位
+        let mut n_digit = 0;
+        let mut group_start = true;
+        let mut i = 0;
+        while i < hex.len() {
+            let c = hex[i];
+            if (self.whitespace && c.is_ascii_whitespace()) || self.separators.contains(&c) {
+                if hi.is_some() {
+                    return Err(DecodeHexError::InvalidChar {
+                        c: c as char,
+                        idx: i,
+                    });
+                }
+                group_start = true;
+                i += 1;
+                continue;
+            }
+            if self.prefix
+                && group_start
+                && c == b'0'
+                && matches!(hex.get(i + 1), Some(b'x' | b'X'))
+            {
+                group_start = false;
+                i += 2;
+                continue;
+            }
+            group_start = false;
+            let v = hc2u8(c, i)?;
+            n_digit += 1;
+            match hi.take() {
+                None => hi = Some(v),
+                Some(h) => res.push(h << 4 | v),
+            }
+            i += 1;
+        }
+        if hi.is_some() {
+            return Err(DecodeHexError::OddLength(n_digit));
+        }
+        Ok(res)
+    }
+}
+
+impl Default for HexParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `HexParser::LENIENT.parse(hex_str)`
+pub fn hex_to_bytes_lenient<T>(hex_str: T) -> Result<Vec<u8>, DecodeHexError>
+where
+    T: AsRef<[u8]>,
+{
+    HexParser::LENIENT.parse(hex_str)
+}
+
+#[inline]
 fn hc2u8(c: u8, idx: usize) -> Result<u8, DecodeHexError> {
     // hex char to u8
-    match c {
-        b'0'..=b'9' => Ok(c - 48), // n @ seems useless
-        b'A'..=b'F' => Ok(c - 55),
-        b'a'..=b'f' => Ok(c - 87),
-        _ => Err(DecodeHexError::InvalidChar { c: c as char, idx }),
+    match DECODE[c as usize] {
+        INVALID_VALUE => Err(DecodeHexError::InvalidChar { c: c as char, idx }),
+        v => Ok(v),
+    }
+}
+
+/// 格式化的时候直接输出 hex, 不用先转成 String, 打日志用
+/// `{}` 和 `{:x}` 是小写, `{:X}` 是大写, `{:#x}` 会加上 `0x`
+#[derive(Clone, Copy, Debug)]
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl Hex<'_> {
+    fn fmt_with(&self, f: &mut fmt::Formatter, table: &[u8; 16]) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        let mut buf = [0; 128];
+        for chunk in self.0.chunks(64) {
+            let n = encode_hex_into(chunk, &mut buf, table);
+            f.write_str(std::str::from_utf8(&buf[..n]).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::LowerHex for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, LOWER_ENCODE)
+    }
+}
+
+impl fmt::UpperHex for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, UPPER_ENCODE)
     }
 }
 
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with(f, LOWER_ENCODE)
+    }
+}
+
+/// 调试密文用, 格式和 `hexdump -C` 一样 (只是最后不打印总长度):
+/// 偏移, 分组的 hex, 可打印的 ASCII (其他的显示成 `.`), 每行以换行结尾
+#[derive(Clone, Copy, Debug)]
+pub struct HexDump<'a> {
+    bytes: &'a [u8],
+    width: usize,
+    group: usize,
+    offset: usize,
+}
+
+impl<'a> HexDump<'a> {
+    /// 每行 16 个字节, 8 个一组
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            width: 16,
+            group: 8,
+            offset: 0,
+        }
+    }
+
+    pub const fn with_width(mut self, width: usize) -> Self {
+        assert!(width > 0);
+        self.width = width;
+        self
+    }
+
+    /// 0 表示不分组
+    pub const fn with_group(mut self, group: usize) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// 第一个字节显示的偏移, 只 dump 一段数据的时候用
+    pub const fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, line) in self.bytes.chunks(self.width).enumerate() {
+            write!(f, "{:08x} ", self.offset + i * self.width)?;
+            for j in 0..self.width {
+                if self.group > 0 && j % self.group == 0 {
+                    f.write_str(" ")?;
+                }
+                match line.get(j) {
+                    Some(b) => write!(f, "{:02x} ", b)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+            f.write_str(" |")?;
+            for &b in line {
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            f.write_str("|\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// `HexDump::new(bytes).to_string()`
+pub fn hexdump(bytes: &[u8]) -> String {
+    HexDump::new(bytes).to_string()
+}
+
 #[test]
 fn test_convert() {
     let bytes = vec![104, 101, 108, 108, 111, 32, 119, 111, 114, 108, 100];
@@ -65,4 +446,205 @@ fn test_convert() {
     assert_eq!(bytes, hex_to_bytes(b"68656C6c6F20776f726C64").unwrap());
 
     assert!(hex_to_bytes(b"68656C6c6F20776f726G64").is_err());
+
+    assert_eq!(
+        toggle_endian(vec!["01020304", "0a0b"]).unwrap(),
+        [4, 3, 2, 1, 0x0b, 0x0a]
+    );
+    assert!(matches!(
+        toggle_endian(vec!["0102", "0g"]),
+        Err(DecodeHexError::InvalidChar { c: 'g', idx: 1 })
+    ));
+}
+
+#[test]
+fn test_hex() {
+    let bytes: Vec<u8> = (0..1000u32).map(|i| (i * 7 + i / 3) as u8).collect();
+    let lower: String = bytes.iter().map(|c| format!("{:02x}", c)).collect();
+    let upper: String = bytes.iter().map(|c| format!("{:02X}", c)).collect();
+    for n in [0, 1, 15, 16, 17, 31, 32, 33, 100, 1000] {
+        assert_eq!(bytes_to_hex(&bytes[..n]), lower[..2 * n]);
+        assert_eq!(bytes_to_hex_upper(&bytes[..n]), upper[..2 * n]);
+        assert_eq!(hex_to_bytes(&lower[..2 * n]).unwrap(), &bytes[..n]);
+        assert_eq!(hex_to_bytes(&upper[..2 * n]).unwrap(), &bytes[..n]);
+    }
+
+    // SIMD 处理的部分出错时, 位置也是对的
+    for idx in [0, 5, 31, 32, 63, 100, 1999] {
+        for c in [b'g', b'G', b'/', b':', b'@', b'`', b' ', 0x80, 0xff] {
+            let mut hex = lower.clone().into_bytes();
+            hex[idx] = c;
+            assert!(matches!(
+                hex_to_bytes(&hex),
+                Err(DecodeHexError::InvalidChar { c: found, idx: i }) if i == idx && found == c as char
+            ));
+        }
+    }
+    assert!(matches!(
+        hex_to_bytes("abc"),
+        Err(DecodeHexError::OddLength(3))
+    ));
+
+    let mut buf = [0; 8];
+    assert_eq!(
+        hex_encode_into(&[0xde, 0xad, 0xbe, 0xef], &mut buf).unwrap(),
+        8
+    );
+    assert_eq!(&buf, b"deadbeef");
+    assert_eq!(hex_encode_upper_into(&[0xde, 0xad], &mut buf).unwrap(), 4);
+    assert_eq!(&buf[..4], b"DEAD");
+    let mut out = [0; 4];
+    assert_eq!(hex_decode_into(b"DeadBeef", &mut out).unwrap(), 4);
+    assert_eq!(out, [0xde, 0xad, 0xbe, 0xef]);
+
+    // out 不够长
+    assert!(matches!(
+        hex_encode_into(&[0xde, 0xad, 0xbe, 0xef], &mut buf[..7]),
+        Err(ConvError::OutputTooSmall { len: 7, needed: 8 })
+    ));
+    assert!(matches!(
+        hex_encode_upper_into(&[0xde, 0xad], &mut [0; 3]),
+        Err(ConvError::OutputTooSmall { len: 3, needed: 4 })
+    ));
+    assert!(matches!(
+        hex_decode_into(b"deadbeef", &mut out[..3]),
+        Err(DecodeHexError::OutputTooSmall { len: 3, needed: 4 })
+    ));
+
+    let hex = Hex(&bytes);
+    assert_eq!(format!("{}", hex), lower);
+    assert_eq!(format!("{:x}", hex), lower);
+    assert_eq!(format!("{:X}", hex), upper);
+    assert_eq!(format!("{:#x}", Hex(&[1, 0xab])), "0x01ab");
+    assert_eq!(format!("{}", Hex(&[])), "");
+}
+
+#[test]
+fn test_hex_lenient() {
+    let bytes = [0xde, 0xad, 0xbe, 0xef];
+    for hex in [
+        "deadbeef",
+        "DeAdBeEf",
+        "0xdeadbeef",
+        "0XDEADBEEF",
+        "de:ad:be:ef",
+        "DE-AD-BE-EF",
+        "dead beef",
+        "  de ad\n\tbe ef\r\n",
+        "0xde 0xad 0xbe 0xef",
+        "0xdead:0xbeef",
+    ] {
+        assert_eq!(hex_to_bytes_lenient(hex).unwrap(), bytes, "{:?}", hex);
+    }
+    assert_eq!(hex_to_bytes_lenient("").unwrap(), []);
+    assert_eq!(hex_to_bytes_lenient("0x").unwrap(), []);
+
+    // 分隔符不能把一个字节拆开
+    assert!(matches!(
+        hex_to_bytes_lenient("de:a:dbe"),
+        Err(DecodeHexError::InvalidChar { c: ':', idx: 4 })
+    ));
+    assert_eq!(
+        hex_to_bytes_lenient("0xdead be").unwrap(),
+        [0xde, 0xad, 0xbe]
+    );
+    assert!(matches!(
+        hex_to_bytes_lenient("dead 0xbeeg"),
+        Err(DecodeHexError::InvalidChar { c: 'g', idx: 10 })
+    ));
+    assert!(matches!(
+        hex_to_bytes_lenient("de ad b"),
+        Err(DecodeHexError::OddLength(5))
+    ));
+    // 0x 只能在一组的开头
+    assert!(matches!(
+        hex_to_bytes_lenient("de0xad"),
+        Err(DecodeHexError::InvalidChar { c: 'x', idx: 3 })
+    ));
+
+    assert!(HexParser::STRICT.parse("de:ad").is_err());
+    assert!(HexParser::STRICT.parse("0xdead").is_err());
+    assert_eq!(HexParser::STRICT.parse("dead").unwrap(), [0xde, 0xad]);
+    let p = HexParser::new().with_separators(b",").with_prefix(false);
+    assert_eq!(p.parse("de, ad,be").unwrap(), [0xde, 0xad, 0xbe]);
+    assert!(p.parse("de:ad").is_err());
+}
+
+#[test]
+fn test_hexdump() {
+    assert_eq!(hexdump(b""), "");
+    assert_eq!(
+        hexdump(b"hello world\nthis is a longer line!!"),
+        "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 74 68 69 73  |hello world.this|\n\
+         00000010  20 69 73 20 61 20 6c 6f  6e 67 65 72 20 6c 69 6e  | is a longer lin|\n\
+         00000020  65 21 21                                          |e!!|\n"
+    );
+    assert_eq!(
+        HexDump::new(&[0, 0x7f, 0x80, b'A', b'~'])
+            .with_width(4)
+            .with_group(2)
+            .with_offset(0x100)
+            .to_string(),
+        "00000100  00 7f  80 41  |...A|\n00000104  7e            |~|\n"
+    );
+}
+
+#[test]
+fn test_endian() {
+    let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+    assert_eq!(u16::read_be(&bytes[..2]).unwrap(), 0x0102);
+    assert_eq!(u16::read_le(&bytes[..2]).unwrap(), 0x0201);
+    assert_eq!(u32::read_be(&bytes[..4]).unwrap(), 0x01020304);
+    assert_eq!(u64::read_le(&bytes[..8]).unwrap(), 0x0807060504030201);
+    assert_eq!(
+        u128::read_be(&bytes).unwrap(),
+        0x0102030405060708090a0b0c0d0e0f10
+    );
+    assert!(matches!(
+        u32::read_be(&bytes[..3]),
+        Err(ConvError::InvalidLength {
+            expected: 4,
+            found: 3
+        })
+    ));
+
+    let mut out = [0; 8];
+    0xdeadbeefu32.write_be(&mut out[..4]);
+    0xdeadbeefu32.write_le(&mut out[4..]);
+    assert_eq!(out, [0xde, 0xad, 0xbe, 0xef, 0xef, 0xbe, 0xad, 0xde]);
+    assert_eq!(<u64 as FixedInt>::SIZE, 8);
+
+    let x = BigUint::from(0x010203u32);
+    assert_eq!(biguint_to_be(&x, 5).unwrap(), [0, 0, 1, 2, 3]);
+    assert_eq!(biguint_to_le(&x, 5).unwrap(), [3, 2, 1, 0, 0]);
+    assert_eq!(biguint_to_be(&BigUint::from(0u8), 2).unwrap(), [0, 0]);
+    assert_eq!(biguint_to_be(&BigUint::from(0u8), 0).unwrap(), []);
+    assert!(matches!(
+        biguint_to_be(&x, 2),
+        Err(ConvError::TooLarge { len: 3, max: 2 })
+    ));
+    assert_eq!(biguint_from_be(&[0, 0, 1, 2, 3]), x);
+    assert_eq!(biguint_from_le(&[3, 2, 1]), x);
+    assert_eq!(biguint_from_be(&[]), BigUint::from(0u8));
+
+    // DER 整数开头的 0x00 可以去掉
+    assert_eq!(pad_left(&[0, 0xff, 0xee], 2).unwrap(), [0xff, 0xee]);
+    assert_eq!(pad_left(&[0xff], 3).unwrap(), [0, 0, 0xff]);
+    assert!(pad_left(&[1, 0xff, 0xee], 2).is_err());
+
+    let mut nonce = bytes;
+    swap_words(&mut nonce, 4).unwrap();
+    assert_eq!(nonce[..8], [4, 3, 2, 1, 8, 7, 6, 5]);
+    swap_words(&mut nonce, 4).unwrap();
+    assert_eq!(nonce, bytes);
+    swap_words(&mut nonce, 16).unwrap();
+    assert_eq!(
+        u128::read_le(&nonce).unwrap(),
+        u128::read_be(&bytes).unwrap()
+    );
+    assert!(matches!(
+        swap_words(&mut nonce, 5),
+        Err(ConvError::NotMultiple { len: 16, word: 5 })
+    ));
+    assert!(swap_words(&mut nonce, 0).is_err());
 }
@@ -0,0 +1,212 @@
+//! 最简单的 DER 编解码, 只支持密钥格式用到的几种类型
+
+use crate::error::DecodeKeyError;
+use num_bigint::BigUint;
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_NULL: u8 = 0x05;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+
+pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let n = content.len();
+    let mut res = Vec::with_capacity(n + 6);
+    res.push(tag);
+    if n < 0x80 {
+        res.push(n as u8);
+    } else {
+        // long form: 0x80 | 长度所占的字节数, 再接长度 (big endian)
+        let bytes = n.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        res.push(0x80 | (bytes.len() - skip) as u8);
+        res.extend(&bytes[skip..]);
+    }
+    res.extend(content);
+    res
+}
+
+pub(crate) fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &items.concat())
+}
+
+/// 非负整数, 最高位为 1 时需要补一个 0x00
+pub(crate) fn uint(x: &BigUint) -> Vec<u8> {
+    let mut bytes = x.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0);
+    }
+    tlv(TAG_INTEGER, &bytes)
+}
+
+pub(crate) fn oid(content: &[u8]) -> Vec<u8> {
+    tlv(TAG_OID, content)
+}
+
+pub(crate) fn null() -> Vec<u8> {
+    tlv(TAG_NULL, &[])
+}
+
+pub(crate) fn octet_string(content: &[u8]) -> Vec<u8> {
+    tlv(TAG_OCTET_STRING, content)
+}
+
+/// unused bits 固定为 0
+pub(crate) fn bit_string(content: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(content.len() + 1);
+    bytes.push(0);
+    bytes.extend(content);
+    tlv(TAG_BIT_STRING, &bytes)
+}
+
+pub(crate) struct DerReader<'a> {
+    data: &'a [u8],
+    idx: usize,
+    offset: usize, // data 在最外层输入中的位置, 报错用
+}
+
+impl<'a> DerReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            idx: 0,
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.idx == self.data.len()
+    }
+
+    /// 读完之后不能有多余的数据
+    pub(crate) fn finish(&self) -> Result<(), DecodeKeyError> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(DecodeKeyError::TrailingData(self.offset + self.idx)),
+        }
+    }
+
+    pub(crate) fn read(&mut self, tag: u8) -> Result<&'a [u8], DecodeKeyError> {
+        let start = self.offset + self.idx;
+        let found = self.next_byte()?;
+        if found != tag {
+            return Err(DecodeKeyError::UnexpectedTag {
+                expected: tag,
+                found,
+                idx: start,
+            });
+        }
+        let n = match self.next_byte()? {
+            n if n < 0x80 => n as usize,
+            0x80 => return Err(DecodeKeyError::InvalidLength(start + 1)),
+            n => {
+                let k = (n & 0x7f) as usize;
+                if k > std::mem::size_of::<usize>() {
+                    return Err(DecodeKeyError::InvalidLength(start + 1));
+                }
+                let mut len = 0;
+                for _ in 0..k {
+                    len = len << 8 | self.next_byte()? as usize;
+                }
+                len
+            }
+        };
+        if n > self.data.len() - self.idx {
+            return Err(DecodeKeyError::UnexpectedEnd);
+        }
+        let content = &self.data[self.idx..self.idx + n];
+        self.idx += n;
+        Ok(content)
+    }
+
+    pub(crate) fn read_sequence(&mut self) -> Result<DerReader<'a>, DecodeKeyError> {
+        let content = self.read(TAG_SEQUENCE)?;
+        Ok(Self {
+            data: content,
+            idx: 0,
+            offset: self.offset + self.idx - content.len(),
+        })
+    }
+
+    pub(crate) fn read_uint(&mut self) -> Result<BigUint, DecodeKeyError> {
+        let start = self.offset + self.idx;
+        let content = self.read(TAG_INTEGER)?;
+        if content.is_empty() || content[0] & 0x80 != 0 {
+            return Err(DecodeKeyError::InvalidInteger(start));
+        }
+        Ok(BigUint::from_bytes_be(content))
+    }
+
+    pub(crate) fn read_null(&mut self) -> Result<(), DecodeKeyError> {
+        let start = self.offset + self.idx;
+        match self.read(TAG_NULL)?.is_empty() {
+            true => Ok(()),
+            false => Err(DecodeKeyError::InvalidLength(start + 1)),
+        }
+    }
+
+    /// 只接受 unused bits 为 0 的
+    pub(crate) fn read_bit_string(&mut self) -> Result<&'a [u8], DecodeKeyError> {
+        let start = self.offset + self.idx;
+        match self.read(TAG_BIT_STRING)?.split_first() {
+            Some((0, rest)) => Ok(rest),
+            _ => Err(DecodeKeyError::InvalidBitString(start)),
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<u8, DecodeKeyError> {
+        match self.data.get(self.idx) {
+            Some(&b) => {
+                self.idx += 1;
+                Ok(b)
+            }
+            None => Err(DecodeKeyError::UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_der() {
+        assert_eq!(tlv(TAG_OCTET_STRING, &[1, 2]), [0x04, 0x02, 1, 2]);
+        let long = tlv(TAG_OCTET_STRING, &[0; 0x1234]);
+        assert_eq!(long[..4], [0x04, 0x82, 0x12, 0x34]);
+
+        assert_eq!(uint(&BigUint::from(0x7fu8)), [0x02, 0x01, 0x7f]);
+        assert_eq!(uint(&BigUint::from(0x80u8)), [0x02, 0x02, 0x00, 0x80]);
+        assert_eq!(uint(&BigUint::from(0u8)), [0x02, 0x01, 0x00]);
+
+        let der = sequence(&[uint(&BigUint::from(65537u32)), null(), long]);
+        let mut reader = DerReader::new(&der);
+        let mut seq = reader.read_sequence().unwrap();
+        assert!(reader.finish().is_ok());
+        assert_eq!(seq.read_uint().unwrap(), BigUint::from(65537u32));
+        assert!(seq.read_null().is_ok());
+        assert_eq!(seq.read(TAG_OCTET_STRING).unwrap().len(), 0x1234);
+        assert!(seq.finish().is_ok());
+
+        assert!(matches!(
+            DerReader::new(&[0x02, 0x01, 0x80]).read_uint(),
+            Err(DecodeKeyError::InvalidInteger(0))
+        ));
+        assert!(matches!(
+            DerReader::new(&[0x30, 0x03, 0x02, 0x01, 0x01, 0x00])
+                .read_sequence()
+                .unwrap()
+                .read(TAG_NULL),
+            Err(DecodeKeyError::UnexpectedTag {
+                expected: TAG_NULL,
+                found: TAG_INTEGER,
+                idx: 2
+            })
+        ));
+        assert!(matches!(
+            DerReader::new(&[0x04, 0x05, 0x00]).read(TAG_OCTET_STRING),
+            Err(DecodeKeyError::UnexpectedEnd)
+        ));
+    }
+}
@@ -1,15 +1,21 @@
-use crate::conv::hex_to_bytes;
+use crate::conv::{biguint_to_be, hex_to_bytes_lenient};
+use crate::error::{DecodeHexError, EcError};
+use crate::hash::{Digest, Hmac, Sha256, Sha384, Sha512};
 use num_bigint::{BigInt, BigUint, Sign, ToBigInt};
 use num_integer::Integer;
 use num_primes::{Generator, Verification};
 use num_traits::{One, Zero}; // ,
+use std::marker::PhantomData;
 use std::ops::Deref;
 
+use crate::ecc_arith::{BigField, Curve, Secp256k1Field};
+pub use crate::ecc_field::FieldElement;
+
 /// 有限域上的点, 负数表示无穷远点
 #[derive(PartialEq, Clone, Debug)]
 pub struct Point {
-    x: BigInt,
-    y: BigInt,
+    pub(crate) x: BigInt,
+    pub(crate) y: BigInt,
 }
 
 impl Point {
@@ -76,42 +82,62 @@ pub fn calc_inv(mut x: BigInt, p: &BigInt) -> Option<BigInt> {
     }
 }
 
+/// 点乘法用的有限域实现, 已知的素数用定长的 limb 运算, 其他的用 BigInt
+enum Arith {
+    Big(Curve<BigField>),
+    Secp256k1(Curve<Secp256k1Field>),
+}
+
+macro_rules! dispatch {
+    ($self:ident.$f:ident($($arg:expr),*)) => {
+        match &$self.arith {
+            Arith::Big(c) => c.$f($($arg),*),
+            Arith::Secp256k1(c) => c.$f($($arg),*),
+        }
+    };
+}
+
 pub struct EcBase {
-    p: BigInt,
-    a: BigInt,
-    b: BigInt,
+    pub(crate) p: BigInt,
+    pub(crate) a: BigInt,
+    pub(crate) b: BigInt,
+    arith: Arith,
 }
 
 impl EcBase {
-    pub fn new(p: BigUint, a: BigInt, b: BigInt) -> Self {
-        // assert p is prime
-        assert!(Verification::is_prime(&p));
-        Self {
-            p: p.to_bigint().unwrap(),
-            a,
-            b,
+    pub fn new(p: BigUint, a: BigInt, b: BigInt) -> Result<Self, EcError> {
+        if !Verification::is_prime(&p) {
+            return Err(EcError::ModulusNotPrime);
         }
+        Ok(Self::new_unchecked(p.to_bigint().unwrap(), a, b))
     }
 
     pub fn new_unchecked(p: BigInt, a: BigInt, b: BigInt) -> Self {
-        Self { p, a, b }
+        let arith = if p == Secp256k1Field::modulus() {
+            Arith::Secp256k1(Curve::new(Secp256k1Field::new(), &a, &b))
+        } else {
+            Arith::Big(Curve::new(BigField::new(p.clone()), &a, &b))
+        };
+        Self { p, a, b, arith }
     }
 
     pub fn calc_inv(&self, x: BigInt) -> Option<BigInt> {
         calc_inv(x, &self.p)
     }
 
+    /// 斜率; None 表示结果是无穷远点.
+    /// p 不是素数 (`new_unchecked`) 时分母可能不可逆, 和 `to_affine` 一样也当作无穷远点
     fn calc_lambda(&self, p1: &Point, p2: &Point) -> Option<BigInt> {
         if p1 != p2 {
             if p1.x == p2.x {
                 None
             } else {
-                Some((&p2.y - &p1.y) * calc_inv(&p2.x - &p1.x, &self.p).unwrap())
+                Some((&p2.y - &p1.y) * calc_inv(&p2.x - &p1.x, &self.p)?)
             }
         } else if p1.y.is_zero() {
             None
         } else {
-            Some((3 * &p1.x * &p1.x + &self.a) * calc_inv(&p1.y + &p1.y, &self.p).unwrap())
+            Some((3 * &p1.x * &p1.x + &self.a) * calc_inv(&p1.y + &p1.y, &self.p)?)
         }
     }
 
@@ -146,7 +172,43 @@ impl EcBase {
         }
     }
 
+    /// 用 Jacobian 坐标做 double-and-add, 最后只求一次逆
+    pub fn mul_binary(&self, k: &BigInt, p: &Point) -> Point {
+        dispatch!(self.mul_binary(k, p))
+    }
+
+    /// wNAF: 非零位平均 w + 1 位才有一个, 负数位直接减去对应的点
     pub fn mul(&self, k: &BigInt, p: &Point) -> Point {
+        dispatch!(self.mul(k, p))
+    }
+
+    /// Montgomery ladder, 私钥之类的秘密标量用: 固定做 bits 轮, 每轮都是一次加法和一次倍乘,
+    /// 用完备的加法公式 (不区分相等/无穷远点), 交换也不看 bit 走分支;
+    /// k 必须小于 2^bits. NOTE: 只有 secp256k1 (定长的 `FieldElement`) 的域运算是常数时间的,
+    /// 其他曲线用 BigInt, 加减法和 cswap 的耗时都和数据有关, 这里只保证算法的结构不依赖 k;
+    /// 见 `is_constant_time`
+    pub fn mul_ladder(&self, k: &BigInt, p: &Point, bits: usize) -> Point {
+        dispatch!(self.mul_ladder(k, p, bits))
+    }
+
+    /// a * p + b * q, Strauss (Shamir's trick): 两个 wNAF 共用同一串倍乘
+    pub fn mul2(&self, a: &BigInt, p: &Point, b: &BigInt, q: &Point) -> Point {
+        dispatch!(self.mul2(a, p, b, q))
+    }
+
+    /// `mul_ladder` (以及 `Ec` 里用它的 `mul_secret`, `ecdh`, `gen_key`) 是否是常数时间的;
+    /// 目前只有 secp256k1 是. 不包括 `sig_gen`: nonce 求逆和算 s 都是 BigInt 运算
+    pub fn is_constant_time(&self) -> bool {
+        matches!(self.arith, Arith::Secp256k1(_))
+    }
+
+    /// k * g, 用预计算的 comb 表 (第一次调用时按 g 计算, 之后 g 不能变)
+    pub(crate) fn mul_comb(&self, k: &BigInt, g: &Point, bits: usize) -> Point {
+        dispatch!(self.mul_comb(k, g, bits))
+    }
+
+    /// 仿射坐标的 double-and-add, 每次加法都要求逆, 留着做对比
+    pub fn mul_affine(&self, k: &BigInt, p: &Point) -> Point {
         // assert!(k.sign() != Sign::Minus);
         if k.is_zero() {
             Point::zero()
@@ -174,12 +236,81 @@ impl EcBase {
     }
 }
 
-pub fn i_from_hex4(s: &str) -> BigInt {
-    BigInt::from_bytes_be(Sign::Plus, &hex_to_bytes(s.replace(' ', "")).unwrap())
+/// RFC 6979 2.3.2 bits2int: 比 qlen 长的只要最左边的 qlen 位, 短的直接当整数.
+/// ECDSA 里消息哈希换成整数也是这样 (SEC 1 4.1.3 第 5 步)
+fn bits2int(data: &[u8], qlen: usize) -> BigInt {
+    let x = BigInt::from_bytes_be(Sign::Plus, data);
+    let blen = data.len() * 8;
+    if blen > qlen {
+        x >> (blen - qlen)
+    } else {
+        x
+    }
+}
+
+/// RFC 6979 3.2 的 HMAC-DRBG: 用私钥和消息哈希做种子, 依次生成 [1, n - 1] 里的 nonce
+struct Rfc6979<H: Digest> {
+    k: Vec<u8>,
+    v: Vec<u8>,
+    n: BigInt,
+    _hash: PhantomData<H>,
+}
+
+impl<H: Digest> Rfc6979<H> {
+    fn new(n: &BigInt, pri_key: &BigInt, hash: &[u8]) -> Self {
+        let rlen = n.bits().div_ceil(8);
+        let int2octets = |x: &BigInt| biguint_to_be(&x.to_biguint().unwrap(), rlen).unwrap();
+        let x = int2octets(&mod_p(pri_key.clone(), n));
+        let h = int2octets(&mod_p(bits2int(hash, n.bits()), n)); // bits2octets
+        let mut drbg = Self {
+            k: vec![0x00; H::OUTPUT_LEN],
+            v: vec![0x01; H::OUTPUT_LEN],
+            n: n.clone(),
+            _hash: PhantomData,
+        };
+        for b in [0x00, 0x01] {
+            drbg.k = drbg.hmac(&[&drbg.v, &[b][..], &x, &h]);
+            drbg.v = drbg.hmac(&[&drbg.v]);
+        }
+        drbg
+    }
+
+    fn hmac(&self, data: &[&[u8]]) -> Vec<u8> {
+        let mut h = Hmac::<H>::new(&self.k);
+        for d in data {
+            h.update(d);
+        }
+        h.finalize()
+    }
+
+    /// 取 qlen 位, 不在 [1, n - 1] 就更新 K, V 再来一次;
+    /// 返回之前先更新好, 下一次调用 (签名要重试的时候) 直接接着生成
+    fn next(&mut self) -> BigInt {
+        let qlen = self.n.bits();
+        loop {
+            let mut t = Vec::with_capacity(qlen.div_ceil(8) + H::OUTPUT_LEN);
+            while t.len() * 8 < qlen {
+                self.v = self.hmac(&[&self.v]);
+                t.extend(&self.v);
+            }
+            let k = bits2int(&t, qlen);
+            self.k = self.hmac(&[&self.v, &[0x00]]);
+            self.v = self.hmac(&[&self.v]);
+            if !k.is_zero() && k < self.n {
+                return k;
+            }
+        }
+    }
+}
+
+/// 解析 4 字节一组, 用空格分开的 hex (标准文档里常见的写法),
+/// 其他 `HexParser::LENIENT` 能接受的格式也可以
+pub fn i_from_hex4(s: &str) -> Result<BigInt, DecodeHexError> {
+    Ok(BigInt::from_bytes_be(Sign::Plus, &hex_to_bytes_lenient(s)?))
 }
 
-pub fn u_from_hex4(s: &str) -> BigUint {
-    BigUint::from_bytes_be(&hex_to_bytes(s.replace(' ', "")).unwrap())
+pub fn u_from_hex4(s: &str) -> Result<BigUint, DecodeHexError> {
+    Ok(BigUint::from_bytes_be(&hex_to_bytes_lenient(s)?))
 }
 
 pub struct Ec {
@@ -187,18 +318,24 @@ pub struct Ec {
     // a: BigInt,
     // b: BigInt,
     ecb: EcBase,
-    n: BigInt, // order
-    g: Point,
+    pub(crate) n: BigInt, // order
+    pub(crate) g: Point,
 }
 
 impl Ec {
-    pub fn new(p: BigUint, a: BigInt, b: BigInt, n: BigUint, g: Point) -> Self {
-        assert!(Verification::is_prime(&n));
-        let ecb = EcBase::new(p, a, b);
-        assert!(ecb.contains(&g));
+    pub fn new(p: BigUint, a: BigInt, b: BigInt, n: BigUint, g: Point) -> Result<Self, EcError> {
+        if !Verification::is_prime(&n) {
+            return Err(EcError::OrderNotPrime);
+        }
+        let ecb = EcBase::new(p, a, b)?;
+        if !ecb.contains(&g) {
+            return Err(EcError::NotOnCurve);
+        }
         let n = n.to_bigint().unwrap();
-        assert!(ecb.mul(&n, &g).is_zero());
-        Self { ecb, n, g }
+        if !ecb.mul(&n, &g).is_zero() {
+            return Err(EcError::InvalidOrder);
+        }
+        Ok(Self { ecb, n, g })
     }
 
     pub fn new_unchecked(p: BigInt, a: BigInt, b: BigInt, n: BigInt, g: Point) -> Self {
@@ -215,21 +352,89 @@ impl Ec {
         }
     }
 
+    /// k * p, k 是秘密的 (私钥, nonce) 时用, 见 `EcBase::mul_ladder`;
+    /// 除了 secp256k1, 其他曲线上仍然会通过耗时泄露 k (`is_constant_time`)
+    pub fn mul_secret(&self, k: &BigInt, p: &Point) -> Point {
+        self.mul_ladder(&mod_p(k.clone(), &self.n), p, self.n.bits())
+    }
+
+    /// ECDH 共享的点; 对方的公钥要先检查, 不然不在曲线上的点 (invalid curve attack)
+    /// 会让乘法落在阶很小的另一条曲线上, 逐步泄露私钥; 常数时间的限制同 `mul_secret`
+    pub fn ecdh(&self, pri_key: &BigInt, pub_key: &Point) -> Result<Point, EcError> {
+        if pub_key.is_zero()
+            || pub_key.x >= self.p
+            || pub_key.y >= self.p
+            || !self.contains(pub_key)
+        {
+            return Err(EcError::NotOnCurve);
+        }
+        Ok(self.mul_secret(pri_key, pub_key))
+    }
+
+    /// k * g, 用预计算的 comb 表; 查表的位置和 k 有关, 只能用于公开的标量
+    pub fn mul_g(&self, k: &BigInt) -> Point {
+        self.mul_comb(&mod_p(k.clone(), &self.n), &self.g, self.n.bits())
+    }
+
+    /// 公钥 = pri_key * G, 用 `mul_secret` 计算 (常数时间的限制同上)
     pub fn gen_key(&self) -> (BigInt, Point) {
         let pri_key = self.gen_pri_key();
-        let pub_key = self.mul(&pri_key, &self.g);
+        let pub_key = self.mul_secret(&pri_key, &self.g);
         (pri_key, pub_key)
     }
 
-    pub fn sig_gen(&self, hash_m: &BigInt, pri_key: &BigInt) -> (BigInt, Point) {
+    /// ECDSA 签名, hash 是消息的哈希值 (比 n 长时只取最左边的 n.bits() 位);
+    /// nonce 按 RFC 6979 确定地生成, 不依赖随机数. RFC 6979 的 HMAC 要和消息的哈希一致,
+    /// 这里按 hash 的长度猜: 48 字节用 SHA-384, 64 字节用 SHA-512, 其他用 SHA-256;
+    /// 用的是别的哈希 (比如 SHA3) 时调用 `sig_gen_with::<H>`.
+    /// 返回 (s, R), 同一个私钥和消息总是得到同一个签名.
+    /// NOTE: 不是常数时间的, 任何曲线上都一样: kG 用 `mul_secret` 计算,
+    /// 但 k^-1 (扩展欧几里得) 和 s 是 BigInt 运算, 耗时和 k, pri_key 有关
+    pub fn sig_gen(&self, hash: &[u8], pri_key: &BigInt) -> (BigInt, Point) {
+        match hash.len() {
+            48 => self.sig_gen_with::<Sha384>(hash, pri_key),
+            64 => self.sig_gen_with::<Sha512>(hash, pri_key),
+            _ => self.sig_gen_with::<Sha256>(hash, pri_key),
+        }
+    }
+
+    /// 和 `sig_gen` 一样, RFC 6979 里的 HMAC 用 H (一般和消息的哈希一致)
+    pub fn sig_gen_with<H: Digest>(&self, hash: &[u8], pri_key: &BigInt) -> (BigInt, Point) {
+        let mut drbg = Rfc6979::<H>::new(&self.n, pri_key, hash);
+        self.sig_gen_nonce(hash, pri_key, || drbg.next())
+    }
+
+    /// nonce 用 `gen_pri_key` 随机生成, 签名每次都不一样
+    pub fn sig_gen_random(&self, hash: &[u8], pri_key: &BigInt) -> (BigInt, Point) {
+        self.sig_gen_nonce(hash, pri_key, || self.gen_pri_key())
+    }
+
+    /// RFC 6979 生成的第一个 nonce
+    pub fn nonce_rfc6979<H: Digest>(&self, hash: &[u8], pri_key: &BigInt) -> BigInt {
+        Rfc6979::<H>::new(&self.n, pri_key, hash).next()
+    }
+
+    /// e = bits2int(hash), r = x(kG) mod n, s = k^-1 * (e + r * pri_key) mod n;
+    /// r 或 s 为 0 时换下一个 k, n 不是素数 (`new_unchecked`) 时 k 不可逆也换
+    fn sig_gen_nonce(
+        &self,
+        hash: &[u8],
+        pri_key: &BigInt,
+        mut next_k: impl FnMut() -> BigInt,
+    ) -> (BigInt, Point) {
+        let hash_m = bits2int(hash, self.n.bits());
         loop {
-            let r = self.gen_pri_key();
-            let p = self.mul(&r, &self.g);
+            let k = next_k();
+            let p = self.mul_secret(&k, &self.g);
             let xr = &p.x % &self.n;
             if xr.is_zero() {
                 continue;
             }
-            let s = calc_inv(r, &self.n).unwrap() * (hash_m + xr * pri_key);
+            let k_inv = match calc_inv(k, &self.n) {
+                Some(k_inv) => k_inv,
+                None => continue,
+            };
+            let s = mod_p(k_inv * (&hash_m + xr * pri_key), &self.n);
             if s.is_zero() {
                 continue;
             }
@@ -237,34 +442,228 @@ impl Ec {
         }
     }
 
-    pub fn sig_ver(&self, hash_m: &BigInt, pub_key: &Point, s: BigInt, pr: Point) -> bool {
-        let s_inv = calc_inv(s, &self.n).unwrap();
-        let pv = self.add(
-            &self.mul(&(hash_m * &s_inv), &self.g),
-            &self.mul(&(&pr.x * s_inv), pub_key),
-        );
+    /// hash 和签名时一样按 bits2int 截取
+    pub fn sig_ver(&self, hash: &[u8], pub_key: &Point, s: BigInt, pr: Point) -> bool {
+        // s 不可逆 (比如 0) 的签名直接判为无效
+        let s_inv = match calc_inv(s, &self.n) {
+            Some(s_inv) => s_inv,
+            None => return false,
+        };
+        let u1 = mod_p(bits2int(hash, self.n.bits()) * &s_inv, &self.n);
+        let u2 = mod_p(&pr.x * s_inv, &self.n);
+        let pv = self.mul2(&u1, &self.g, &u2, pub_key);
         pv == pr
     }
 }
 
 impl Ec {
+    /// 标准文档里的参数, 都是常量, unwrap 不会失败
+    fn from_hex4(p: &str, a: &str, b: &str, n: &str, x: &str, y: &str) -> Self {
+        let g = Point::new(i_from_hex4(x).unwrap(), i_from_hex4(y).unwrap());
+        Ec::new_unchecked(
+            i_from_hex4(p).unwrap(),
+            i_from_hex4(a).unwrap(),
+            i_from_hex4(b).unwrap(),
+            i_from_hex4(n).unwrap(),
+            g,
+        )
+    }
+
     pub fn secp256k1() -> Self {
         let p_str = "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE FFFFFC2F";
         let x_str = "79BE667E F9DCBBAC 55A06295 CE870B07 029BFCDB 2DCE28D9 59F2815B 16F81798";
         let y_str = "483ADA77 26A3C465 5DA4FBFC 0E1108A8 FD17B448 A6855419 9C47D08F FB10D4B8";
         let n_str = "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE BAAEDCE6 AF48A03B BFD25E8C D0364141"; // the order
-        let g = Point::new(i_from_hex4(x_str), i_from_hex4(y_str));
 
         // let p = u_from_hex4(p_str);
         // let n = u_from_hex4(n_str);
         // Ec::new(p, BigInt::zero(), BigInt::from(7u8), n, g);
 
-        let p = i_from_hex4(p_str);
-        let n = i_from_hex4(n_str);
-        Ec::new_unchecked(p, BigInt::zero(), BigInt::from(7u8), n, g)
+        Self::from_hex4(p_str, "00", "07", n_str, x_str, y_str)
+    }
+
+    /// NIST P-256 (FIPS 186-4 D.1.2.3), a = p - 3
+    pub fn p256() -> Self {
+        Self::from_hex4(
+            "FFFFFFFF 00000001 00000000 00000000 00000000 FFFFFFFF FFFFFFFF FFFFFFFF",
+            "FFFFFFFF 00000001 00000000 00000000 00000000 FFFFFFFF FFFFFFFF FFFFFFFC",
+            "5AC635D8 AA3A93E7 B3EBBD55 769886BC 651D06B0 CC53B0F6 3BCE3C3E 27D2604B",
+            "FFFFFFFF 00000000 FFFFFFFF FFFFFFFF BCE6FAAD A7179E84 F3B9CAC2 FC632551",
+            "6B17D1F2 E12C4247 F8BCE6E5 63A440F2 77037D81 2DEB33A0 F4A13945 D898C296",
+            "4FE342E2 FE1A7F9B 8EE7EB4A 7C0F9E16 2BCE3357 6B315ECE CBB64068 37BF51F5",
+        )
+    }
+
+    /// SEC 2 里 P-256 的名字
+    pub fn secp256r1() -> Self {
+        Self::p256()
+    }
+
+    /// NIST P-384 (FIPS 186-4 D.1.2.4), a = p - 3
+    pub fn p384() -> Self {
+        Self::from_hex4(
+            "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF \
+             FFFFFFFF FFFFFFFE FFFFFFFF 00000000 00000000 FFFFFFFF",
+            "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF \
+             FFFFFFFF FFFFFFFE FFFFFFFF 00000000 00000000 FFFFFFFC",
+            "B3312FA7 E23EE7E4 988E056B E3F82D19 181D9C6E FE814112 \
+             0314088F 5013875A C656398D 8A2ED19D 2A85C8ED D3EC2AEF",
+            "FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF \
+             C7634D81 F4372DDF 581A0DB2 48B0A77A ECEC196A CCC52973",
+            "AA87CA22 BE8B0537 8EB1C71E F320AD74 6E1D3B62 8BA79B98 \
+             59F741E0 82542A38 5502F25D BF55296C 3A545E38 72760AB7",
+            "3617DE4A 96262C6F 5D9E98BF 9292DC29 F8F41DBD 289A147C \
+             E9DA3113 B5F0B8C0 0A60B1CE 1D7E819D 7A431D7C 90EA0E5F",
+        )
+    }
+
+    /// NIST P-521 (FIPS 186-4 D.1.2.5), p = 2^521 - 1, a = p - 3
+    pub fn p521() -> Self {
+        Self::from_hex4(
+            "01FF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF \
+             FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF",
+            "01FF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF \
+             FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFC",
+            "0051 953EB961 8E1C9A1F 929A21A0 B68540EE A2DA725B 99B315F3 B8B48991 8EF109E1 \
+             56193951 EC7E937B 1652C0BD 3BB1BF07 3573DF88 3D2C34F1 EF451FD4 6B503F00",
+            "01FF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFA \
+             51868783 BF2F966B 7FCC0148 F709A5D0 3BB5C9B8 899C47AE BB6FB71E 91386409",
+            "00C6 858E06B7 0404E9CD 9E3ECB66 2395B442 9C648139 053FB521 F828AF60 6B4D3DBA \
+             A14B5E77 EFE75928 FE1DC127 A2FFA8DE 3348B3C1 856A429B F97E7E31 C2E5BD66",
+            "0118 39296A78 9A3BC004 5C8A5FB4 2C7D1BD9 98F54449 579B4468 17AFBD17 273E662C \
+             97EE7299 5EF42640 C550B901 3FAD0761 353C7086 A272C240 88BE9476 9FD16650",
+        )
+    }
+
+    /// brainpoolP256r1 (RFC 5639 3.4)
+    pub fn brainpool_p256r1() -> Self {
+        Self::from_hex4(
+            "A9FB57DB A1EEA9BC 3E660A90 9D838D72 6E3BF623 D5262028 2013481D 1F6E5377",
+            "7D5A0975 FC2C3057 EEF67530 417AFFE7 FB8055C1 26DC5C6C E94A4B44 F330B5D9",
+            "26DC5C6C E94A4B44 F330B5D9 BBD77CBF 95841629 5CF7E1CE 6BCCDC18 FF8C07B6",
+            "A9FB57DB A1EEA9BC 3E660A90 9D838D71 8C397AA3 B561A6F7 901E0E82 974856A7",
+            "8BD2AEB9 CB7E57CB 2C4B482F FC81B7AF B9DE27E1 E3BD23C2 3A4453BD 9ACE3262",
+            "547EF835 C3DAC4FD 97F8461A 14611DC9 C2774513 2DED8E54 5C1D54C7 2F046997",
+        )
+    }
+
+    /// brainpoolP384r1 (RFC 5639 3.6)
+    pub fn brainpool_p384r1() -> Self {
+        Self::from_hex4(
+            "8CB91E82 A3386D28 0F5D6F7E 50E641DF 152F7109 ED5456B4 \
+             12B1DA19 7FB71123 ACD3A729 901D1A71 87470013 3107EC53",
+            "7BC382C6 3D8C150C 3C72080A CE05AFA0 C2BEA28E 4FB22787 \
+             139165EF BA91F90F 8AA5814A 503AD4EB 04A8C7DD 22CE2826",
+            "04A8C7DD 22CE2826 8B39B554 16F0447C 2FB77DE1 07DCD2A6 \
+             2E880EA5 3EEB62D5 7CB43902 95DBC994 3AB78696 FA504C11",
+            "8CB91E82 A3386D28 0F5D6F7E 50E641DF 152F7109 ED5456B3 \
+             1F166E6C AC0425A7 CF3AB6AF 6B7FC310 3B883202 E9046565",
+            "1D1C64F0 68CF45FF A2A63A81 B7C13F6B 8847A3E7 7EF14FE3 \
+             DB7FCAFE 0CBD10E8 E826E034 36D646AA EF87B2E2 47D4AF1E",
+            "8ABE1D75 20F9C2A4 5CB1EB8E 95CFD552 62B70B29 FEEC5864 \
+             E19C054F F9912928 0E464621 77918111 42820341 263C5315",
+        )
+    }
+
+    /// brainpoolP512r1 (RFC 5639 3.7)
+    pub fn brainpool_p512r1() -> Self {
+        Self::from_hex4(
+            "AADD9DB8 DBE9C48B 3FD4E6AE 33C9FC07 CB308DB3 B3C9D20E D6639CCA 70330871 \
+             7D4D9B00 9BC66842 AECDA12A E6A380E6 2881FF2F 2D82C685 28AA6056 583A48F3",
+            "7830A331 8B603B89 E2327145 AC234CC5 94CBDD8D 3DF91610 A83441CA EA9863BC \
+             2DED5D5A A8253AA1 0A2EF1C9 8B9AC8B5 7F1117A7 2BF2C7B9 E7C1AC4D 77FC94CA",
+            "3DF91610 A83441CA EA9863BC 2DED5D5A A8253AA1 0A2EF1C9 8B9AC8B5 7F1117A7 \
+             2BF2C7B9 E7C1AC4D 77FC94CA DC083E67 984050B7 5EBAE5DD 2809BD63 8016F723",
+            "AADD9DB8 DBE9C48B 3FD4E6AE 33C9FC07 CB308DB3 B3C9D20E D6639CCA 70330870 \
+             553E5C41 4CA92619 41866119 7FAC1047 1DB1D381 085DDADD B5879682 9CA90069",
+            "81AEE4BD D82ED964 5A21322E 9C4C6A93 85ED9F70 B5D916C1 B43B62EE F4D0098E \
+             FF3B1F78 E2D0D48D 50D1687B 93B97D5F 7C6D5047 406A5E68 8B352209 BCB9F822",
+            "7DDE385D 566332EC C0EABFA9 CF7822FD F209F700 24A57B1A A000C55B 881F8111 \
+             B2DCDE49 4A5F485E 5BCA4BD8 8A2763AE D1CA2B2F A8F05406 78CD1E0F 3AD80892",
+        )
+    }
+
+    /// SM2 推荐曲线 (GB/T 32918.5-2017), a = p - 3; 签名和加密见 `crate::sm2`
+    pub fn sm2() -> Self {
+        Self::from_hex4(
+            "FFFFFFFE FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF 00000000 FFFFFFFF FFFFFFFF",
+            "FFFFFFFE FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF 00000000 FFFFFFFF FFFFFFFC",
+            "28E9FA9E 9D9F5E34 4D5A9E4B CF6509A7 F39789F5 15AB8F92 DDBCBD41 4D940E93",
+            "FFFFFFFE FFFFFFFF FFFFFFFF FFFFFFFF 7203DF6B 21C6052B 53BBF409 39D54123",
+            "32C4AE2C 1F198119 5F990446 6A39C994 8FE30BBF F2660BE1 715A4589 334C74C7",
+            "BC3736A2 F4F6779C 59BDCEE3 6B692153 D0A9877C C62A4740 02DF32E5 2139F0A0",
+        )
+    }
+
+    /// 按名字查找曲线, 不区分大小写, 比如 "P-256", "secp256r1", "prime256v1"
+    pub fn from_name(name: &str) -> Result<Self, EcError> {
+        CURVES
+            .iter()
+            .find(|c| c.names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+            .map(|c| (c.new)())
+            .ok_or_else(|| EcError::UnknownCurve(name.to_string()))
+    }
+
+    /// 按 OID (点分十进制, 比如 "1.2.840.10045.3.1.7") 查找曲线
+    pub fn from_oid(oid: &str) -> Result<Self, EcError> {
+        CURVES
+            .iter()
+            .find(|c| c.oid == oid)
+            .map(|c| (c.new)())
+            .ok_or_else(|| EcError::UnknownCurve(oid.to_string()))
     }
 }
 
+/// 已知的曲线: 名字 (第一个是标准名), OID 和构造函数
+pub struct NamedCurve {
+    pub names: &'static [&'static str],
+    pub oid: &'static str,
+    pub new: fn() -> Ec,
+}
+
+pub const CURVES: &[NamedCurve] = &[
+    NamedCurve {
+        names: &["secp256k1"],
+        oid: "1.3.132.0.10",
+        new: Ec::secp256k1,
+    },
+    NamedCurve {
+        names: &["P-256", "secp256r1", "prime256v1"],
+        oid: "1.2.840.10045.3.1.7",
+        new: Ec::p256,
+    },
+    NamedCurve {
+        names: &["P-384", "secp384r1"],
+        oid: "1.3.132.0.34",
+        new: Ec::p384,
+    },
+    NamedCurve {
+        names: &["P-521", "secp521r1"],
+        oid: "1.3.132.0.35",
+        new: Ec::p521,
+    },
+    NamedCurve {
+        names: &["brainpoolP256r1"],
+        oid: "1.3.36.3.3.2.8.1.1.7",
+        new: Ec::brainpool_p256r1,
+    },
+    NamedCurve {
+        names: &["brainpoolP384r1"],
+        oid: "1.3.36.3.3.2.8.1.1.11",
+        new: Ec::brainpool_p384r1,
+    },
+    NamedCurve {
+        names: &["brainpoolP512r1"],
+        oid: "1.3.36.3.3.2.8.1.1.13",
+        new: Ec::brainpool_p512r1,
+    },
+    NamedCurve {
+        names: &["SM2", "sm2p256v1"],
+        oid: "1.2.156.10197.1.301",
+        new: Ec::sm2,
+    },
+];
+
 impl Deref for Ec {
     type Target = EcBase;
 
@@ -299,20 +698,186 @@ mod test {
     fn test_pub_key_gen() {
         let ec = Ec::secp256k1();
         let pri_key =
-            i_from_hex4("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD");
-        let pub_x = i_from_hex4("F028892BAD7ED57D2FB57BF33081D5CFCF6F9ED3D3D7F159C2E2FFF579DC341A");
-        let pub_y = i_from_hex4("07CF33DA18BD734C600B96A72BBC4749D5141C90EC8AC328AE52DDFE2E505BDB");
+            i_from_hex4("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD")
+                .unwrap();
+        let pub_x = i_from_hex4("F028892BAD7ED57D2FB57BF33081D5CFCF6F9ED3D3D7F159C2E2FFF579DC341A")
+            .unwrap();
+        let pub_y = i_from_hex4("07CF33DA18BD734C600B96A72BBC4749D5141C90EC8AC328AE52DDFE2E505BDB")
+            .unwrap();
         let pub_key = ec.mul(&pri_key, &ec.g);
         assert_eq!(pub_key, Point::new(pub_x, pub_y));
     }
 
+    #[test]
+    fn test_eth_address() {
+        // address = keccak256(x || y) 的后 20 个字节
+        use crate::conv::bytes_to_hex;
+        use crate::hash::{Digest, Keccak256};
+        let ec = Ec::secp256k1();
+        let pub_key = ec.mul(&BigInt::one(), &ec.g);
+        let mut h = Keccak256::new();
+        h.update(&pub_key.x.to_bytes_be().1);
+        h.update(&pub_key.y.to_bytes_be().1);
+        assert_eq!(
+            bytes_to_hex(&h.finalize()[12..]),
+            "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+    }
+
+    #[test]
+    fn test_segwit_address() {
+        // BIP-173 / BIP-350 中的例子, 公钥都是私钥 1 对应的 G
+        use crate::bech32::encode_segwit;
+        use crate::hash::hash160;
+        let ec = Ec::secp256k1();
+        let pub_key = ec.mul(&BigInt::one(), &ec.g);
+        let x = pub_key.x.to_bytes_be().1;
+
+        // P2WPKH: 压缩公钥的 hash160
+        let mut compressed = vec![if pub_key.y.is_even() { 0x02 } else { 0x03 }];
+        compressed.extend(&x);
+        assert_eq!(
+            encode_segwit("bc", 0, &hash160(&compressed)).unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+
+        // P2TR: 直接用 x 坐标 (没有 tweak)
+        assert_eq!(
+            encode_segwit("bc", 1, &x).unwrap(),
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+    }
+
+    #[test]
+    fn test_checked() {
+        // NOTE: is_prime 对很小的素数偶尔会误判, 所以这里用 secp256k1 的参数
+        let p =
+            u_from_hex4("FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE FFFFFC2F")
+                .unwrap();
+        let n =
+            u_from_hex4("FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE BAAEDCE6 AF48A03B BFD25E8C D0364141")
+                .unwrap();
+        let ec = Ec::secp256k1();
+        let new = |n: &BigUint, g: &Point| {
+            Ec::new(
+                p.clone(),
+                BigInt::zero(),
+                BigInt::from(7u8),
+                n.clone(),
+                g.clone(),
+            )
+        };
+        assert!(new(&n, &ec.g).is_ok());
+        let g = Point::new(ec.g.x.clone(), &ec.g.y + 1);
+        assert!(matches!(new(&n, &g), Err(EcError::NotOnCurve)));
+        assert!(matches!(new(&p, &ec.g), Err(EcError::InvalidOrder)));
+        assert!(matches!(
+            new(&(&n + 1u8), &ec.g),
+            Err(EcError::OrderNotPrime)
+        ));
+        assert!(matches!(
+            EcBase::new(BigUint::from(15u8), BigInt::zero(), BigInt::from(7u8)),
+            Err(EcError::ModulusNotPrime)
+        ));
+
+        assert!(matches!(
+            u_from_hex4("0102 030"),
+            Err(DecodeHexError::OddLength(7))
+        ));
+        assert_eq!(
+            u_from_hex4("0102 0304").unwrap(),
+            BigUint::from(0x01020304u32)
+        );
+
+        // s = 0 不可逆, 验签失败而不是 panic
+        let (_, pub_key) = ec.gen_key();
+        assert!(!ec.sig_ver(&[1], &pub_key, BigInt::zero(), ec.g.clone()));
+    }
+
+    #[test]
+    fn test_jacobian() {
+        let ec = Ec::secp256k1();
+        let k = i_from_hex4("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD")
+            .unwrap();
+        assert_eq!(ec.mul_binary(&k, &ec.g), ec.mul_affine(&k, &ec.g));
+        for k in 0..40u32 {
+            let k = BigInt::from(k);
+            assert_eq!(ec.mul_binary(&k, &ec.g), ec.mul_affine(&k, &ec.g));
+        }
+        // n * G, (n - 1) * G + G 都是无穷远点
+        assert!(ec.mul(&ec.n, &ec.g).is_zero());
+        let g_neg = ec.mul(&(&ec.n - 1u8), &ec.g);
+        assert_eq!(g_neg, ec.sub(&Point::zero(), &ec.g));
+        assert!(ec.add(&g_neg, &ec.g).is_zero());
+        assert!(ec.mul(&k, &Point::zero()).is_zero());
+
+        // a != 0 的小曲线: y^2 = x^3 + 2x + 2 (mod 17), G = (5, 1) 的阶为 19
+        let ecb = EcBase::new_unchecked(BigInt::from(17u8), BigInt::from(2u8), BigInt::from(2u8));
+        let g = Point::new_i32(5, 1);
+        for k in 0..40u32 {
+            let k = BigInt::from(k);
+            assert_eq!(ecb.mul_binary(&k, &g), ecb.mul_affine(&k, &g));
+            assert_eq!(ecb.mul(&k, &g), ecb.mul_affine(&k, &g));
+        }
+        assert!(ecb.mul(&BigInt::from(19u8), &g).is_zero());
+
+        // p 不是素数时分母可能不可逆, 当作无穷远点而不是 panic
+        let ecb = EcBase::new_unchecked(BigInt::from(15u8), BigInt::one(), BigInt::one());
+        assert!(ecb
+            .add(&Point::new_i32(0, 1), &Point::new_i32(3, 2))
+            .is_zero());
+        assert!(ecb
+            .add(&Point::new_i32(1, 5), &Point::new_i32(1, 5))
+            .is_zero());
+        ecb.mul(&BigInt::from(7u8), &Point::new_i32(1, 5));
+        ecb.mul_affine(&BigInt::from(7u8), &Point::new_i32(1, 5));
+    }
+
     #[test]
     fn test_ecdsa() {
         let ec = Ec::secp256k1();
         let (pri_key, pub_key) = ec.gen_key();
-        let hash_m = Generator::new_uint(256).to_bigint().unwrap();
-        let (s, pr) = ec.sig_gen(&hash_m, &pri_key);
-        dbg!(ec.sig_ver(&hash_m, &pub_key, s, pr));
+        let mut hash = Sha256::digest(b"abc");
+        let (s, pr) = ec.sig_gen(&hash, &pri_key);
+        assert!(ec.sig_ver(&hash, &pub_key, s.clone(), pr.clone()));
+        hash[31] ^= 1;
+        assert!(!ec.sig_ver(&hash, &pub_key, s, pr));
+
+        // n 不是素数时有的 k 不可逆, 换下一个 k 而不是 panic
+        let n = num_traits::pow(BigInt::from(6u8), 99);
+        let ec6 = Ec::new_unchecked(ec.p.clone(), ec.a.clone(), ec.b.clone(), n, ec.g.clone());
+        for i in 0..16u8 {
+            let (s, _) = ec6.sig_gen(&[i], &pri_key);
+            assert!(!s.is_zero() && s < ec6.n);
+        }
+    }
+
+    #[test]
+    fn test_precomputed() {
+        let ec = Ec::secp256k1();
+        let (_, q) = ec.gen_key();
+        let k = i_from_hex4("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD")
+            .unwrap();
+        let expected = ec.mul_binary(&k, &ec.g);
+        assert_eq!(ec.mul(&k, &ec.g), expected);
+        assert_eq!(ec.mul_g(&k), expected);
+        // 超过 n 的也可以
+        assert_eq!(ec.mul_g(&(&k + &ec.n)), expected);
+        for k in 0..40u32 {
+            let k = BigInt::from(k);
+            assert_eq!(ec.mul_g(&k), ec.mul_binary(&k, &ec.g));
+            assert_eq!(ec.mul(&k, &q), ec.mul_binary(&k, &q));
+        }
+        assert!(ec.mul_g(&ec.n).is_zero());
+
+        let a = &k * 3u8 % &ec.n;
+        assert_eq!(
+            ec.mul2(&k, &ec.g, &a, &q),
+            ec.add(&ec.mul_binary(&k, &ec.g), &ec.mul_binary(&a, &q))
+        );
+        // a * G + (n - a) * G = 0
+        assert!(ec.mul2(&a, &ec.g, &(&ec.n - &a), &ec.g).is_zero());
+        assert_eq!(ec.mul2(&k, &ec.g, &BigInt::zero(), &q), expected);
     }
 
     #[test]
@@ -320,6 +885,229 @@ mod test {
         let ec = Ec::secp256k1();
         let (pri_key1, pub_key1) = ec.gen_key();
         let (pri_key2, pub_key2) = ec.gen_key();
-        assert_eq!(ec.mul(&pri_key1, &pub_key2), ec.mul(&pri_key2, &pub_key1));
+        let shared = ec.ecdh(&pri_key1, &pub_key2).unwrap();
+        assert_eq!(shared, ec.ecdh(&pri_key2, &pub_key1).unwrap());
+        assert_eq!(shared, ec.mul(&pri_key1, &pub_key2));
+
+        // 无穷远点, 不在曲线上的点, 坐标没有约简的点都要拒绝
+        let bad = [
+            Point::zero(),
+            Point::new_i32(1, 1),
+            Point::new(&pub_key2.x + &ec.p, pub_key2.y.clone()),
+            Point::new(pub_key2.x.clone(), &pub_key2.y + &ec.p),
+        ];
+        for p in &bad {
+            assert!(matches!(ec.ecdh(&pri_key1, p), Err(EcError::NotOnCurve)));
+        }
+    }
+
+    #[test]
+    fn test_ladder() {
+        let ec = Ec::secp256k1();
+        let (_, q) = ec.gen_key();
+        let k = i_from_hex4("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD")
+            .unwrap();
+        assert_eq!(ec.mul_secret(&k, &ec.g), ec.mul_binary(&k, &ec.g));
+        assert_eq!(ec.mul_secret(&k, &q), ec.mul_binary(&k, &q));
+        for k in 0..20u32 {
+            let k = BigInt::from(k);
+            assert_eq!(ec.mul_secret(&k, &q), ec.mul_binary(&k, &q));
+        }
+        assert!(ec.mul_secret(&ec.n, &q).is_zero());
+        assert!(ec.mul_secret(&BigInt::one(), &Point::zero()).is_zero());
+        let n1 = &ec.n - 1u8;
+        assert_eq!(ec.mul_secret(&n1, &q), ec.mul_binary(&n1, &q));
+
+        // a != 0: y^2 = x^3 + 2x + 2 (mod 17), G = (5, 1) 的阶为 19
+        let ecb = EcBase::new_unchecked(BigInt::from(17u8), BigInt::from(2u8), BigInt::from(2u8));
+        let g = Point::new_i32(5, 1);
+        for k in 0..19u32 {
+            let k = BigInt::from(k);
+            assert_eq!(ecb.mul_ladder(&k, &g, 5), ecb.mul_affine(&k, &g));
+        }
+    }
+
+    #[test]
+    fn test_named_curves() {
+        for name in [
+            "P-256",
+            "P-384",
+            "P-521",
+            "brainpoolP256r1",
+            "brainpoolP384r1",
+            "brainpoolP512r1",
+            "SM2",
+        ] {
+            let ec = Ec::from_name(name).unwrap();
+            // 参数都要通过检查
+            let p = ec.p.to_biguint().unwrap();
+            let n = ec.n.to_biguint().unwrap();
+            assert!(Ec::new(p, ec.a.clone(), ec.b.clone(), n, ec.g.clone()).is_ok());
+
+            let (pri_key, pub_key) = ec.gen_key();
+            assert!(ec.contains(&pub_key));
+            assert_eq!(pub_key, ec.mul_g(&pri_key));
+            // SHA-512 比 P-256 等的 n 长, 要截取
+            let hash = Sha512::digest(name.as_bytes());
+            let (s, pr) = ec.sig_gen(&hash, &pri_key);
+            assert!(ec.sig_ver(&hash, &pub_key, s, pr));
+        }
+
+        // P-256 的 2G 和 3G
+        let ec = Ec::secp256r1();
+        let g2 = Point::new(
+            i_from_hex4("7CF27B188D034F7E8A52380304B51AC3C08969E277F21B35A60B48FC47669978")
+                .unwrap(),
+            i_from_hex4("07775510DB8ED040293D9AC69F7430DBBA7DADE63CE982299E04B79D227873D1")
+                .unwrap(),
+        );
+        let g3 = Point::new(
+            i_from_hex4("5ECBE4D1A6330A44C8F7EF951D4BF165E6C6B721EFADA985FB41661BC6E7FD6C")
+                .unwrap(),
+            i_from_hex4("8734640C4998FF7E374B06CE1A64A2ECD82AB036384FB83D9A79B127A27D5032")
+                .unwrap(),
+        );
+        assert_eq!(ec.mul(&BigInt::from(2u8), &ec.g), g2);
+        assert_eq!(ec.mul_secret(&BigInt::from(3u8), &ec.g), g3);
+    }
+
+    #[test]
+    fn test_registry() {
+        assert!(Ec::secp256k1().is_constant_time());
+        assert!(!Ec::p256().is_constant_time());
+
+        let ec = Ec::from_name("prime256v1").unwrap();
+        assert_eq!(ec.g, Ec::p256().g);
+        assert_eq!(Ec::from_name("p-384").unwrap().n, Ec::p384().n);
+        assert_eq!(Ec::from_oid("1.3.132.0.35").unwrap().n, Ec::p521().n);
+        assert_eq!(Ec::from_oid("1.3.132.0.10").unwrap().g, Ec::secp256k1().g);
+        assert_eq!(Ec::from_name("sm2p256v1").unwrap().g, Ec::sm2().g);
+        assert_eq!(
+            Ec::from_oid("1.3.36.3.3.2.8.1.1.13").unwrap().n,
+            Ec::brainpool_p512r1().n
+        );
+        assert_eq!(
+            Ec::from_oid("1.2.840.10045.3.1.7").unwrap().p,
+            Ec::secp256r1().p
+        );
+        assert!(matches!(
+            Ec::from_name("P-255"),
+            Err(EcError::UnknownCurve(name)) if name == "P-255"
+        ));
+        assert!(Ec::from_oid("1.2.3").is_err());
+        for c in CURVES {
+            assert!(Ec::from_name(c.names[0]).is_ok());
+            assert!(Ec::from_oid(c.oid).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rfc6979() {
+        let h = |m: &str| Sha256::digest(m.as_bytes());
+        let hex = |s: &str| i_from_hex4(s).unwrap();
+
+        // RFC 6979 A.2.5, P-256 + SHA-256
+        let ec = Ec::p256();
+        let x = hex("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721");
+        let pub_key = ec.mul_g(&x);
+        assert_eq!(
+            pub_key,
+            Point::new(
+                hex("60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6"),
+                hex("7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299")
+            )
+        );
+        for (m, k, r, s) in [
+            (
+                "sample",
+                "A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+                "EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716",
+                "F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8",
+            ),
+            (
+                "test",
+                "D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0",
+                "F1ABB023518351CD71D881567B1EA663ED3EFCF6C5132B354F28D3B0B7D38367",
+                "019F4113742A2B14BD25926B49C649155F267E60D3814B4C0CC84250E46F0083",
+            ),
+        ] {
+            assert_eq!(ec.nonce_rfc6979::<Sha256>(&h(m), &x), hex(k));
+            let (sig_s, pr) = ec.sig_gen(&h(m), &x);
+            assert_eq!(&pr.x % &ec.n, hex(r));
+            assert_eq!(sig_s, hex(s));
+            assert!(ec.sig_ver(&h(m), &pub_key, sig_s, pr));
+        }
+
+        // 同一节的 P-256 + SHA-512: 哈希比 n 长, 要按 bits2int 截取
+        let hash = Sha512::digest(b"sample");
+        assert_eq!(
+            ec.nonce_rfc6979::<Sha512>(&hash, &x),
+            hex("5FA81C63109BADB88C1F367B47DA606DA28CAD69AA22C4FE6AD7DF73A7173AA5")
+        );
+        let (sig_s, pr) = ec.sig_gen_with::<Sha512>(&hash, &x);
+        // 64 字节的哈希, sig_gen 默认也用 HMAC-SHA512
+        assert_eq!(ec.sig_gen(&hash, &x), (sig_s.clone(), pr.clone()));
+        assert_eq!(
+            &pr.x % &ec.n,
+            hex("8496A60B5E9B47C825488827E0495B0E3FA109EC4568FD3F8D1097678EB97F00")
+        );
+        assert_eq!(
+            sig_s,
+            hex("2362AB1ADBE2B8ADF9CB9EDAB740EA6049C028114F2460F96554F61FAE3302FE")
+        );
+        assert!(ec.sig_ver(&hash, &pub_key, sig_s, pr));
+
+        // secp256k1 + SHA-256 (比特币钱包常用的测试数据)
+        let ec = Ec::secp256k1();
+        for (x, m, k) in [
+            (
+                "01",
+                "Satoshi Nakamoto",
+                "8F8A276C19F4149656B280621E358CCE24F5F52542772691EE69063B74F15D15",
+            ),
+            (
+                "01",
+                "All those moments will be lost in time, like tears in rain. Time to die...",
+                "38AA22D72376B4DBC472E06C3BA403EE0A394DA63FC58D88686C611ABA98D6B3",
+            ),
+            (
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364140",
+                "Satoshi Nakamoto",
+                "33A19B60E25FB6F4435AF53A3D42D493644827367E6453928554F43E49AA6F90",
+            ),
+            (
+                "F8B8AF8CE3C7CCA5E300D33939540C10D45CE001B8F252BFBC57BA0342904181",
+                "Alan Turing",
+                "525A82B70E67874398067543FD84C83D30C175FDC45FDEEE082FE13B1D7CFDF1",
+            ),
+        ] {
+            assert_eq!(ec.nonce_rfc6979::<Sha256>(&h(m), &hex(x)), hex(k));
+        }
+        let x = BigInt::one();
+        let (s, pr) = ec.sig_gen(&h("Satoshi Nakamoto"), &x);
+        assert_eq!(
+            &pr.x % &ec.n,
+            hex("934B1EA10A4B3C1757E2B0C017D0B6143CE3C9A7E6A4A49860D7A6AB210EE3D8")
+        );
+        // 这里没有规范化成比特币要求的 low-s, 得到的是 n - low_s
+        let low_s = hex("2442CE9D2B916064108014783E923EC36B49743E2FFA1C4496F01A512AAFD9E5");
+        assert_eq!(
+            s,
+            hex("DBBD3162D46E9F9BEF7FEB87C16DC13B4F6568A87F4E83F728E2443BA586675C")
+        );
+        assert_eq!(&s + &low_s, ec.n);
+
+        // 确定性: 同样的输入同样的签名; 随机 nonce 的签名也能验证
+        let hash_m = h("sample");
+        assert_eq!(ec.sig_gen(&hash_m, &x), ec.sig_gen(&hash_m, &x));
+        let (s, pr) = ec.sig_gen_random(&hash_m, &x);
+        assert!(ec.sig_ver(&hash_m, &ec.mul_g(&x), s, pr));
+
+        // 拒绝一个 nonce 之后接着生成的也符合 RFC 6979 3.2 h.3
+        let mut drbg = Rfc6979::<Sha256>::new(&ec.n, &x, &hash_m);
+        let k1 = drbg.next();
+        let k2 = drbg.next();
+        assert_eq!(k1, ec.nonce_rfc6979::<Sha256>(&hash_m, &x));
+        assert_ne!(k1, k2);
     }
 }
@@ -0,0 +1,667 @@
+//! 椭圆曲线的内部运算: Jacobian / 射影坐标, wNAF, comb, Montgomery ladder;
+//! 对有限域泛型, 任意素数用 BigInt, 已知的素数 (secp256k1) 用定长的 FieldElement
+
+use crate::ecc::{calc_inv, mod_p, Point};
+use crate::ecc_field::FieldElement;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+use std::sync::OnceLock;
+
+/// 变基点乘法 wNAF 的窗口大小, 预计算 2^(w-2) 个奇数倍点
+const WNAF_WINDOW: u32 = 5;
+/// 固定基点 comb 的齿数, 预计算 2^w - 1 个点
+const COMB_TEETH: usize = 8;
+
+/// 曲线运算用到的有限域操作, 输入输出都已经约简到 [0, p)
+pub(crate) trait Field {
+    type Elem: Clone + PartialEq + Send + Sync;
+
+    fn zero(&self) -> Self::Elem;
+    fn one(&self) -> Self::Elem;
+    fn is_zero(&self, x: &Self::Elem) -> bool;
+    fn add(&self, x: &Self::Elem, y: &Self::Elem) -> Self::Elem;
+    fn sub(&self, x: &Self::Elem, y: &Self::Elem) -> Self::Elem;
+    fn mul(&self, x: &Self::Elem, y: &Self::Elem) -> Self::Elem;
+    fn neg(&self, x: &Self::Elem) -> Self::Elem;
+    fn inv(&self, x: &Self::Elem) -> Option<Self::Elem>;
+    /// bit 为 1 时交换, 不走分支
+    fn cswap(&self, bit: u8, x: &mut Self::Elem, y: &mut Self::Elem);
+    /// 任意整数, 先 mod p
+    fn to_elem(&self, x: &BigInt) -> Self::Elem;
+    fn to_int(&self, x: &Self::Elem) -> BigInt;
+
+    #[inline]
+    fn sqr(&self, x: &Self::Elem) -> Self::Elem {
+        self.mul(x, x)
+    }
+
+    #[inline]
+    fn dbl(&self, x: &Self::Elem) -> Self::Elem {
+        self.add(x, x)
+    }
+}
+
+/// 任意素数 p, 每次运算后 mod p
+pub(crate) struct BigField {
+    p: BigInt,
+}
+
+impl BigField {
+    pub(crate) fn new(p: BigInt) -> Self {
+        Self { p }
+    }
+}
+
+impl Field for BigField {
+    type Elem = BigInt;
+
+    fn zero(&self) -> BigInt {
+        BigInt::zero()
+    }
+
+    fn one(&self) -> BigInt {
+        BigInt::one()
+    }
+
+    fn is_zero(&self, x: &BigInt) -> bool {
+        x.is_zero()
+    }
+
+    fn add(&self, x: &BigInt, y: &BigInt) -> BigInt {
+        let r = x + y;
+        if r >= self.p {
+            r - &self.p
+        } else {
+            r
+        }
+    }
+
+    fn sub(&self, x: &BigInt, y: &BigInt) -> BigInt {
+        let r = x - y;
+        if r.sign() == Sign::Minus {
+            r + &self.p
+        } else {
+            r
+        }
+    }
+
+    fn mul(&self, x: &BigInt, y: &BigInt) -> BigInt {
+        x * y % &self.p
+    }
+
+    fn neg(&self, x: &BigInt) -> BigInt {
+        if x.is_zero() {
+            BigInt::zero()
+        } else {
+            &self.p - x
+        }
+    }
+
+    fn inv(&self, x: &BigInt) -> Option<BigInt> {
+        calc_inv(x.clone(), &self.p)
+    }
+
+    /// 用算术代替分支: x += (y - x) * bit, y -= (y - x) * bit;
+    /// NOTE: BigInt 本身的运算不是常数时间的
+    fn cswap(&self, bit: u8, x: &mut BigInt, y: &mut BigInt) {
+        let t = (&*y - &*x) * BigInt::from(bit);
+        *x += &t;
+        *y -= t;
+    }
+
+    fn to_elem(&self, x: &BigInt) -> BigInt {
+        mod_p(x.clone(), &self.p)
+    }
+
+    fn to_int(&self, x: &BigInt) -> BigInt {
+        x.clone()
+    }
+}
+
+/// secp256k1 的素数域, 见 `FieldElement`
+pub(crate) struct Secp256k1Field {
+    p: BigInt,
+}
+
+impl Secp256k1Field {
+    pub(crate) fn new() -> Self {
+        Self { p: Self::modulus() }
+    }
+
+    pub(crate) fn modulus() -> BigInt {
+        (BigInt::one() << 256) - (BigInt::one() << 32) - 977
+    }
+}
+
+impl Field for Secp256k1Field {
+    type Elem = FieldElement;
+
+    fn zero(&self) -> FieldElement {
+        FieldElement::ZERO
+    }
+
+    fn one(&self) -> FieldElement {
+        FieldElement::ONE
+    }
+
+    fn is_zero(&self, x: &FieldElement) -> bool {
+        x.is_zero()
+    }
+
+    fn add(&self, x: &FieldElement, y: &FieldElement) -> FieldElement {
+        *x + *y
+    }
+
+    fn sub(&self, x: &FieldElement, y: &FieldElement) -> FieldElement {
+        *x - *y
+    }
+
+    fn mul(&self, x: &FieldElement, y: &FieldElement) -> FieldElement {
+        *x * *y
+    }
+
+    fn neg(&self, x: &FieldElement) -> FieldElement {
+        -*x
+    }
+
+    fn inv(&self, x: &FieldElement) -> Option<FieldElement> {
+        x.invert()
+    }
+
+    fn cswap(&self, bit: u8, x: &mut FieldElement, y: &mut FieldElement) {
+        FieldElement::cswap(bit, x, y)
+    }
+
+    fn to_elem(&self, x: &BigInt) -> FieldElement {
+        let bytes = mod_p(x.clone(), &self.p).to_bytes_be().1;
+        let mut buf = [0; 32];
+        buf[32 - bytes.len()..].copy_from_slice(&bytes);
+        FieldElement::from_bytes_be(&buf)
+    }
+
+    fn to_int(&self, x: &FieldElement) -> BigInt {
+        BigInt::from_bytes_be(Sign::Plus, &x.to_bytes_be())
+    }
+}
+
+/// 仿射坐标, 无穷远点用 None 表示
+#[derive(Clone)]
+struct Affine<E> {
+    x: E,
+    y: E,
+}
+
+/// Jacobian 坐标 (X, Y, Z) 表示仿射坐标的 (X / Z^2, Y / Z^3), Z = 0 是无穷远点;
+/// 加法和倍乘都不需要求逆
+#[derive(Clone)]
+struct Jacobian<E> {
+    x: E,
+    y: E,
+    z: E,
+}
+
+/// 齐次射影坐标 (X, Y, Z) 表示 (X / Z, Y / Z), 无穷远点是 (0, 1, 0); 给完备加法公式用
+#[derive(Clone)]
+struct Projective<E> {
+    x: E,
+    y: E,
+    z: E,
+}
+
+/// 固定基点 g 的 comb 表 (Lim-Lee): 标量按 d 位一段分成 w 段,
+/// table[i] 是 i 的各个 bit 对应的 2^(j * d) * g 之和, 乘法只要 d 次倍乘和 d 次加法
+struct Comb<E> {
+    d: usize,
+    table: Vec<Option<Affine<E>>>,
+}
+
+/// 一条曲线 y^2 = x^3 + ax + b 在某种有限域实现上的运算
+pub(crate) struct Curve<F: Field> {
+    f: F,
+    a: F::Elem,
+    b3: F::Elem, // 3b, 完备加法公式用
+    a_zero: bool,
+    comb: OnceLock<Comb<F::Elem>>, // 第一次用到 g 的乘法时才计算
+}
+
+impl<F: Field> Curve<F> {
+    pub(crate) fn new(f: F, a: &BigInt, b: &BigInt) -> Self {
+        let a = f.to_elem(a);
+        let b3 = f.to_elem(&(3 * b));
+        Self {
+            a_zero: f.is_zero(&a),
+            f,
+            a,
+            b3,
+            comb: OnceLock::new(),
+        }
+    }
+
+    fn affine(&self, p: &Point) -> Option<Affine<F::Elem>> {
+        if p.is_zero() {
+            None
+        } else {
+            Some(Affine {
+                x: self.f.to_elem(&p.x),
+                y: self.f.to_elem(&p.y),
+            })
+        }
+    }
+
+    fn to_point(&self, p: &Option<Affine<F::Elem>>) -> Point {
+        match p {
+            None => Point::zero(),
+            Some(p) => Point::new(self.f.to_int(&p.x), self.f.to_int(&p.y)),
+        }
+    }
+
+    /// 用 Jacobian 坐标做 double-and-add, 最后只求一次逆
+    pub(crate) fn mul_binary(&self, k: &BigInt, p: &Point) -> Point {
+        let p = self.affine(p);
+        let mut res = self.jacobian_zero();
+        for byte in k.to_bytes_be().1 {
+            for i in (0..8).rev() {
+                res = self.double(&res);
+                if (byte >> i) & 1 == 1 {
+                    res = self.add_mixed(&res, &p);
+                }
+            }
+        }
+        self.to_point(&self.to_affine(&res))
+    }
+
+    /// wNAF: 非零位平均 w + 1 位才有一个, 负数位直接减去对应的点
+    pub(crate) fn mul(&self, k: &BigInt, p: &Point) -> Point {
+        let naf = wnaf(k, WNAF_WINDOW);
+        let table = self.odd_multiples(&self.affine(p), WNAF_WINDOW);
+        let mut res = self.jacobian_zero();
+        for &d in naf.iter().rev() {
+            res = self.double(&res);
+            res = self.add_digit(&res, &table, d);
+        }
+        self.to_point(&self.to_affine(&res))
+    }
+
+    /// a * p + b * q, Strauss (Shamir's trick): 两个 wNAF 共用同一串倍乘
+    pub(crate) fn mul2(&self, a: &BigInt, p: &Point, b: &BigInt, q: &Point) -> Point {
+        let naf_a = wnaf(a, WNAF_WINDOW);
+        let naf_b = wnaf(b, WNAF_WINDOW);
+        let table_a = self.odd_multiples(&self.affine(p), WNAF_WINDOW);
+        let table_b = self.odd_multiples(&self.affine(q), WNAF_WINDOW);
+        let mut res = self.jacobian_zero();
+        for i in (0..naf_a.len().max(naf_b.len())).rev() {
+            res = self.double(&res);
+            res = self.add_digit(&res, &table_a, naf_a.get(i).copied().unwrap_or(0));
+            res = self.add_digit(&res, &table_b, naf_b.get(i).copied().unwrap_or(0));
+        }
+        self.to_point(&self.to_affine(&res))
+    }
+
+    /// Montgomery ladder: 固定做 bits 轮, 每轮一次完备加法和一次倍乘, 交换也不看 bit 走分支
+    pub(crate) fn mul_ladder(&self, k: &BigInt, p: &Point, bits: usize) -> Point {
+        let bytes = k.to_bytes_le().1;
+        let mut r0 = self.projective_zero();
+        let mut r1 = match self.affine(p) {
+            None => self.projective_zero(),
+            Some(p) => Projective {
+                x: p.x,
+                y: p.y,
+                z: self.f.one(),
+            },
+        };
+        let mut prev = 0;
+        for i in (0..bits).rev() {
+            let bit = bytes.get(i / 8).map_or(0, |b| (b >> (i % 8)) & 1);
+            self.cswap(bit ^ prev, &mut r0, &mut r1);
+            prev = bit;
+            r1 = self.add_complete(&r0, &r1);
+            r0 = self.add_complete(&r0, &r0);
+        }
+        self.cswap(prev, &mut r0, &mut r1);
+        self.to_point(&self.projective_to_affine(&r0))
+    }
+
+    /// k * g, k 要先 mod n; comb 表第一次调用时按 g 计算, 之后一直用同一个 g
+    pub(crate) fn mul_comb(&self, k: &BigInt, g: &Point, bits: usize) -> Point {
+        let comb = self.comb.get_or_init(|| self.comb_table(g, bits));
+        let bytes = k.to_bytes_le().1;
+        let bit = |i: usize| bytes.get(i / 8).map_or(0, |b| (b >> (i % 8)) as usize & 1);
+        let mut res = self.jacobian_zero();
+        for j in (0..comb.d).rev() {
+            res = self.double(&res);
+            let idx = (0..COMB_TEETH).fold(0, |idx, i| idx | bit(i * comb.d + j) << i);
+            if idx != 0 {
+                res = self.add_mixed(&res, &comb.table[idx]);
+            }
+        }
+        self.to_point(&self.to_affine(&res))
+    }
+
+    fn comb_table(&self, g: &Point, bits: usize) -> Comb<F::Elem> {
+        let d = bits.div_ceil(COMB_TEETH);
+        let mut base = Vec::with_capacity(COMB_TEETH);
+        let mut p = self.to_jacobian(&self.affine(g));
+        for _ in 0..COMB_TEETH {
+            base.push(p.clone());
+            for _ in 0..d {
+                p = self.double(&p);
+            }
+        }
+        let base = self.to_affine_batch(&base);
+
+        let mut table = vec![self.jacobian_zero(); 1 << COMB_TEETH];
+        for i in 1..table.len() {
+            // 去掉最高位之后的那个已经算好了
+            let high = usize::BITS - 1 - i.leading_zeros();
+            table[i] = self.add_mixed(&table[i ^ (1 << high)], &base[high as usize]);
+        }
+        Comb {
+            d,
+            table: self.to_affine_batch(&table),
+        }
+    }
+
+    fn jacobian_zero(&self) -> Jacobian<F::Elem> {
+        Jacobian {
+            x: self.f.one(),
+            y: self.f.one(),
+            z: self.f.zero(),
+        }
+    }
+
+    fn projective_zero(&self) -> Projective<F::Elem> {
+        Projective {
+            x: self.f.zero(),
+            y: self.f.one(),
+            z: self.f.zero(),
+        }
+    }
+
+    fn to_jacobian(&self, p: &Option<Affine<F::Elem>>) -> Jacobian<F::Elem> {
+        match p {
+            None => self.jacobian_zero(),
+            Some(p) => Jacobian {
+                x: p.x.clone(),
+                y: p.y.clone(),
+                z: self.f.one(),
+            },
+        }
+    }
+
+    fn to_affine(&self, p: &Jacobian<F::Elem>) -> Option<Affine<F::Elem>> {
+        let z_inv = self.f.inv(&p.z)?;
+        Some(self.scale(p, &z_inv))
+    }
+
+    #[inline]
+    fn scale(&self, p: &Jacobian<F::Elem>, z_inv: &F::Elem) -> Affine<F::Elem> {
+        let f = &self.f;
+        let z_inv2 = f.sqr(z_inv);
+        Affine {
+            x: f.mul(&p.x, &z_inv2),
+            y: f.mul(&p.y, &f.mul(&z_inv2, z_inv)),
+        }
+    }
+
+    /// Montgomery's trick: 一批点转成仿射坐标只求一次逆
+    fn to_affine_batch(&self, ps: &[Jacobian<F::Elem>]) -> Vec<Option<Affine<F::Elem>>> {
+        let f = &self.f;
+        let mut prefix = Vec::with_capacity(ps.len());
+        let mut acc = f.one();
+        for p in ps {
+            prefix.push(acc.clone());
+            if !f.is_zero(&p.z) {
+                acc = f.mul(&acc, &p.z);
+            }
+        }
+        // z 都不为 0, p 是素数时乘积一定可逆;
+        // p 不是素数 (`new_unchecked`) 时可能不可逆, 退回到一个个地转
+        let mut inv = match f.inv(&acc) {
+            Some(inv) => inv,
+            None => return ps.iter().map(|p| self.to_affine(p)).collect(),
+        };
+        let mut res = vec![None; ps.len()];
+        for (i, p) in ps.iter().enumerate().rev() {
+            if f.is_zero(&p.z) {
+                continue;
+            }
+            let z_inv = f.mul(&inv, &prefix[i]);
+            inv = f.mul(&inv, &p.z);
+            res[i] = Some(self.scale(p, &z_inv));
+        }
+        res
+    }
+
+    /// [p, 3p, 5p, ..., (2^(w-1) - 1)p]
+    fn odd_multiples(&self, p: &Option<Affine<F::Elem>>, w: u32) -> Vec<Option<Affine<F::Elem>>> {
+        let n = 1 << (w - 2);
+        let p2 = self.to_affine(&self.double(&self.to_jacobian(p)));
+        let mut res = Vec::with_capacity(n);
+        res.push(self.to_jacobian(p));
+        for i in 1..n {
+            res.push(self.add_mixed(&res[i - 1], &p2));
+        }
+        self.to_affine_batch(&res)
+    }
+
+    /// 加上 wNAF 的一位 d (奇数, 可以是负数) 对应的点
+    #[inline]
+    fn add_digit(
+        &self,
+        r: &Jacobian<F::Elem>,
+        table: &[Option<Affine<F::Elem>>],
+        d: i8,
+    ) -> Jacobian<F::Elem> {
+        match d {
+            0 => r.clone(),
+            d if d > 0 => self.add_mixed(r, &table[(d as usize - 1) / 2]),
+            d => {
+                let p = table[(-d as usize - 1) / 2].as_ref().map(|p| Affine {
+                    x: p.x.clone(),
+                    y: self.f.neg(&p.y),
+                });
+                self.add_mixed(r, &p)
+            }
+        }
+    }
+
+    /// dbl-2007-bl, a 任意
+    fn double(&self, p: &Jacobian<F::Elem>) -> Jacobian<F::Elem> {
+        let f = &self.f;
+        if f.is_zero(&p.z) || f.is_zero(&p.y) {
+            return self.jacobian_zero();
+        }
+        let xx = f.sqr(&p.x);
+        let yy = f.sqr(&p.y);
+        let yyyy = f.sqr(&yy);
+        let s = f.dbl(&f.dbl(&f.mul(&p.x, &yy)));
+        let mut m = f.add(&f.dbl(&xx), &xx);
+        if !self.a_zero {
+            let zz = f.sqr(&p.z);
+            m = f.add(&m, &f.mul(&self.a, &f.sqr(&zz)));
+        }
+        let x3 = f.sub(&f.sqr(&m), &f.dbl(&s));
+        let yyyy8 = f.dbl(&f.dbl(&f.dbl(&yyyy)));
+        let y3 = f.sub(&f.mul(&m, &f.sub(&s, &x3)), &yyyy8);
+        let z3 = f.dbl(&f.mul(&p.y, &p.z));
+        Jacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Jacobian + 仿射 (Z2 = 1), 比两个 Jacobian 相加少几次乘法
+    fn add_mixed(&self, p1: &Jacobian<F::Elem>, p2: &Option<Affine<F::Elem>>) -> Jacobian<F::Elem> {
+        let f = &self.f;
+        let p2 = match p2 {
+            None => return p1.clone(),
+            Some(p2) => p2,
+        };
+        if f.is_zero(&p1.z) {
+            return Jacobian {
+                x: p2.x.clone(),
+                y: p2.y.clone(),
+                z: f.one(),
+            };
+        }
+        let z1z1 = f.sqr(&p1.z);
+        let u2 = f.mul(&p2.x, &z1z1);
+        let s2 = f.mul(&p2.y, &f.mul(&p1.z, &z1z1));
+        let h = f.sub(&u2, &p1.x);
+        let r = f.sub(&s2, &p1.y);
+        if f.is_zero(&h) {
+            // x 相同: 同一个点就倍乘, 互为相反数就是无穷远点
+            return if f.is_zero(&r) {
+                self.double(p1)
+            } else {
+                self.jacobian_zero()
+            };
+        }
+        let hh = f.sqr(&h);
+        let hhh = f.mul(&h, &hh);
+        let v = f.mul(&p1.x, &hh);
+        let x3 = f.sub(&f.sub(&f.sqr(&r), &hhh), &f.dbl(&v));
+        let y3 = f.sub(&f.mul(&r, &f.sub(&v, &x3)), &f.mul(&p1.y, &hhh));
+        let z3 = f.mul(&p1.z, &h);
+        Jacobian {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Renes-Costello-Batina 2015 的 Algorithm 1, 对任意 a 都完备 (包括 p1 == p2 和无穷远点)
+    fn add_complete(
+        &self,
+        p1: &Projective<F::Elem>,
+        p2: &Projective<F::Elem>,
+    ) -> Projective<F::Elem> {
+        let f = &self.f;
+        let t0 = f.mul(&p1.x, &p2.x);
+        let t1 = f.mul(&p1.y, &p2.y);
+        let t2 = f.mul(&p1.z, &p2.z);
+        let t3 = f.mul(&f.add(&p1.x, &p1.y), &f.add(&p2.x, &p2.y));
+        let t3 = f.sub(&t3, &f.add(&t0, &t1));
+        let t4 = f.mul(&f.add(&p1.x, &p1.z), &f.add(&p2.x, &p2.z));
+        let t4 = f.sub(&t4, &f.add(&t0, &t2));
+        let t5 = f.mul(&f.add(&p1.y, &p1.z), &f.add(&p2.y, &p2.z));
+        let t5 = f.sub(&t5, &f.add(&t1, &t2));
+        // a = 0 时省掉和 a 相乘的几项
+        let (a_t4, a_t2) = if self.a_zero {
+            (f.zero(), f.zero())
+        } else {
+            (f.mul(&self.a, &t4), f.mul(&self.a, &t2))
+        };
+        let z3 = f.add(&a_t4, &f.mul(&self.b3, &t2));
+        let x3 = f.sub(&t1, &z3);
+        let z3 = f.add(&t1, &z3);
+        let y3 = f.mul(&x3, &z3);
+        let t1 = f.add(&f.add(&f.dbl(&t0), &t0), &a_t2);
+        let mut t4 = f.mul(&self.b3, &t4);
+        if !self.a_zero {
+            t4 = f.add(&t4, &f.mul(&self.a, &f.sub(&t0, &a_t2)));
+        }
+        let y3 = f.add(&y3, &f.mul(&t1, &t4));
+        let x3 = f.sub(&f.mul(&t3, &x3), &f.mul(&t5, &t4));
+        let z3 = f.add(&f.mul(&t5, &z3), &f.mul(&t3, &t1));
+        Projective {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    fn projective_to_affine(&self, p: &Projective<F::Elem>) -> Option<Affine<F::Elem>> {
+        let z_inv = self.f.inv(&p.z)?;
+        Some(Affine {
+            x: self.f.mul(&p.x, &z_inv),
+            y: self.f.mul(&p.y, &z_inv),
+        })
+    }
+
+    fn cswap(&self, bit: u8, a: &mut Projective<F::Elem>, b: &mut Projective<F::Elem>) {
+        self.f.cswap(bit, &mut a.x, &mut b.x);
+        self.f.cswap(bit, &mut a.y, &mut b.y);
+        self.f.cswap(bit, &mut a.z, &mut b.z);
+    }
+}
+
+/// width-w NAF, 低位在前; 非零位都是奇数, |d| < 2^(w-1), 相邻 w 位中最多一个非零.
+/// 直接在 k 的 bit 上做, 每次取 w 位加上进位, 不用 BigInt 运算
+fn wnaf(k: &BigInt, w: u32) -> Vec<i8> {
+    let bytes = k.to_bytes_le().1;
+    let bit = |i: usize| bytes.get(i / 8).map_or(0, |b| (b >> (i % 8)) as i32 & 1);
+    // 多一位给最后的进位
+    let len = bytes.len() * 8 + 1;
+    let mut res = vec![0; len];
+    let (mut i, mut carry) = (0, 0);
+    while i < len {
+        if bit(i) == carry {
+            i += 1;
+            continue;
+        }
+        let now = (w as usize).min(len - i);
+        let mut d = (0..now).fold(0, |d, j| d | bit(i + j) << j) + carry;
+        carry = (d >> (w - 1)) & 1;
+        d -= carry << w;
+        res[i] = d as i8;
+        i += now;
+    }
+    while res.last() == Some(&0) {
+        res.pop();
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wnaf() {
+        for k in [0u64, 1, 2, 7, 31, 32, 0xdead_beef, u64::MAX] {
+            for w in 2..=6 {
+                let naf = wnaf(&BigInt::from(k), w);
+                let v = naf
+                    .iter()
+                    .rev()
+                    .fold(BigInt::zero(), |v, &d| v * 2 + BigInt::from(d));
+                assert_eq!(v, BigInt::from(k));
+                for (i, &d) in naf.iter().enumerate() {
+                    assert!(d == 0 || (d % 2 != 0 && (d.unsigned_abs() as i32) < 1 << (w - 1)));
+                    // d 非零时, 后面的 w - 1 位都是 0
+                    if d != 0 {
+                        assert!(naf[i + 1..].iter().take(w as usize - 1).all(|&d| d == 0));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields_agree() {
+        // 同一条曲线 (secp256k1) 分别用 BigInt 和 FieldElement 计算, 结果要一样
+        let p = Secp256k1Field::modulus();
+        let (a, b) = (BigInt::zero(), BigInt::from(7u8));
+        let big = Curve::new(BigField::new(p), &a, &b);
+        let fe = Curve::new(Secp256k1Field::new(), &a, &b);
+        let g = Point::new(
+            crate::ecc::i_from_hex4(
+                "79BE667E F9DCBBAC 55A06295 CE870B07 029BFCDB 2DCE28D9 59F2815B 16F81798",
+            )
+            .unwrap(),
+            crate::ecc::i_from_hex4(
+                "483ADA77 26A3C465 5DA4FBFC 0E1108A8 FD17B448 A6855419 9C47D08F FB10D4B8",
+            )
+            .unwrap(),
+        );
+        let k = BigInt::from(0x1234_5678_9abc_def0u64) << 190;
+        let q = fe.mul(&k, &g);
+        assert_eq!(q, big.mul(&k, &g));
+        assert_eq!(fe.mul_binary(&k, &q), big.mul_binary(&k, &q));
+        assert_eq!(fe.mul_ladder(&k, &q, 256), big.mul_ladder(&k, &q, 256));
+        assert_eq!(fe.mul_comb(&k, &g, 256), big.mul_comb(&k, &g, 256));
+        assert_eq!(fe.mul2(&k, &g, &k, &q), big.mul2(&k, &g, &k, &q));
+    }
+}
@@ -0,0 +1,294 @@
+//! secp256k1 的素数域 p = 2^256 - 2^32 - 977, 用 4 个 u64 (低位在前) 定长表示;
+//! 利用 2^256 ≡ 2^32 + 977 (mod p) 约简, 运算不分配内存, 也不根据数据走分支
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// p, 低位在前
+const P: [u64; 4] = [
+    0xFFFFFFFE_FFFFFC2F,
+    0xFFFFFFFF_FFFFFFFF,
+    0xFFFFFFFF_FFFFFFFF,
+    0xFFFFFFFF_FFFFFFFF,
+];
+/// 2^256 - p = 2^32 + 977
+const C: u64 = 0x1_000003D1;
+
+/// 模 p 的元素, 始终保持 < p, 所以可以直接比较 limb
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct FieldElement([u64; 4]);
+
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let v = a as u128 + b as u128 + carry as u128;
+    (v as u64, (v >> 64) as u64)
+}
+
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let v = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (v as u64, (v >> 127) as u64)
+}
+
+/// mask 全 1 时选 b, 全 0 时选 a
+#[inline(always)]
+fn select(a: &[u64; 4], b: &[u64; 4], mask: u64) -> [u64; 4] {
+    let mut r = [0; 4];
+    for i in 0..4 {
+        r[i] = a[i] ^ ((a[i] ^ b[i]) & mask);
+    }
+    r
+}
+
+impl FieldElement {
+    pub const ZERO: Self = Self([0; 4]);
+    pub const ONE: Self = Self([1, 0, 0, 0]);
+
+    pub const fn from_u64(x: u64) -> Self {
+        Self([x, 0, 0, 0])
+    }
+
+    /// big endian, 大于等于 p 的会被约简
+    pub fn from_bytes_be(bytes: &[u8; 32]) -> Self {
+        let mut r = [0; 4];
+        for (i, limb) in r.iter_mut().enumerate() {
+            let start = 24 - 8 * i;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        Self::reduce_once(r, 0)
+    }
+
+    pub fn to_bytes_be(&self) -> [u8; 32] {
+        let mut res = [0; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 24 - 8 * i;
+            res[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        res
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().fold(0, |acc, &x| acc | x) == 0
+    }
+
+    /// hi * 2^256 + r 小于 2p 时, 减一次 p 就够了
+    #[inline(always)]
+    fn reduce_once(r: [u64; 4], hi: u64) -> Self {
+        let mut t = [0; 4];
+        let mut borrow = 0;
+        for i in 0..4 {
+            (t[i], borrow) = sbb(r[i], P[i], borrow);
+        }
+        // 有进位, 或者减 p 没有借位, 说明 r >= p
+        let mask = 0u64.wrapping_sub(hi | (borrow ^ 1));
+        Self(select(&r, &t, mask))
+    }
+
+    /// 512 位的乘积约简: lo + hi * 2^256 ≡ lo + hi * C
+    #[inline(always)]
+    fn reduce_wide(t: [u64; 8]) -> Self {
+        let mut r = [0; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let v = t[i] as u128 + t[i + 4] as u128 * C as u128 + carry;
+            r[i] = v as u64;
+            carry = v >> 64;
+        }
+        // carry < 2^34, 再乘一次 C 加回去
+        let mut c;
+        (r[0], c) = adc(r[0], (carry as u64).wrapping_mul(C), 0);
+        (r[1], c) = adc(r[1], ((carry * C as u128) >> 64) as u64, c);
+        (r[2], c) = adc(r[2], 0, c);
+        (r[3], c) = adc(r[3], 0, c);
+        // 还有进位的话 r 已经很小了, 加上 C 不会再溢出
+        (r[0], c) = adc(r[0], C & 0u64.wrapping_sub(c), 0);
+        (r[1], c) = adc(r[1], 0, c);
+        (r[2], c) = adc(r[2], 0, c);
+        (r[3], _) = adc(r[3], 0, c);
+        Self::reduce_once(r, 0)
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// 连续平方 n 次
+    fn pow2k(&self, n: usize) -> Self {
+        let mut r = *self;
+        for _ in 0..n {
+            r = r.square();
+        }
+        r
+    }
+
+    /// 费马小定理: a^(p - 2), 用固定的加法链 (255 次平方, 15 次乘法); 0 没有逆
+    pub fn invert(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let x = *self;
+        // xk = a^(2^k - 1)
+        let x2 = x.square() * x;
+        let x3 = x2.square() * x;
+        let x6 = x3.pow2k(3) * x3;
+        let x9 = x6.pow2k(3) * x3;
+        let x11 = x9.pow2k(2) * x2;
+        let x22 = x11.pow2k(11) * x11;
+        let x44 = x22.pow2k(22) * x22;
+        let x88 = x44.pow2k(44) * x44;
+        let x176 = x88.pow2k(88) * x88;
+        let x220 = x176.pow2k(44) * x44;
+        let x223 = x220.pow2k(3) * x3;
+        // p - 2 = (2^223 - 1) << 33 | (2^22 - 1) << 10 | 0b101101
+        let r = x223.pow2k(23) * x22;
+        let r = r.pow2k(5) * x;
+        let r = r.pow2k(3) * x2;
+        Some(r.pow2k(2) * x)
+    }
+
+    /// bit 为 1 时交换 a 和 b
+    pub fn cswap(bit: u8, a: &mut Self, b: &mut Self) {
+        let mask = 0u64.wrapping_sub(bit as u64 & 1);
+        for i in 0..4 {
+            let t = (a.0[i] ^ b.0[i]) & mask;
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+}
+
+impl Add for FieldElement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut r = [0; 4];
+        let mut carry = 0;
+        for (r, (a, b)) in r.iter_mut().zip(self.0.into_iter().zip(rhs.0)) {
+            (*r, carry) = adc(a, b, carry);
+        }
+        Self::reduce_once(r, carry)
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let mut r = [0; 4];
+        let mut borrow = 0;
+        for (r, (a, b)) in r.iter_mut().zip(self.0.into_iter().zip(rhs.0)) {
+            (*r, borrow) = sbb(a, b, borrow);
+        }
+        // 借位了就加回 p
+        let p = select(&[0; 4], &P, 0u64.wrapping_sub(borrow));
+        let mut carry = 0;
+        for (r, p) in r.iter_mut().zip(p) {
+            (*r, carry) = adc(*r, p, carry);
+        }
+        Self(r)
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut t = [0; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let v = t[i + j] as u128 + self.0[i] as u128 * rhs.0[j] as u128 + carry;
+                t[i + j] = v as u64;
+                carry = v >> 64;
+            }
+            t[i + 4] = carry as u64;
+        }
+        Self::reduce_wide(t)
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ecc::mod_p;
+    use num_bigint::{BigInt, Sign};
+    use num_traits::Zero;
+
+    fn to_int(x: &FieldElement) -> BigInt {
+        BigInt::from_bytes_be(Sign::Plus, &x.to_bytes_be())
+    }
+
+    fn from_int(x: &BigInt) -> FieldElement {
+        let bytes = x.to_bytes_be().1;
+        let mut buf = [0; 32];
+        buf[32 - bytes.len()..].copy_from_slice(&bytes);
+        FieldElement::from_bytes_be(&buf)
+    }
+
+    #[test]
+    fn test_field() {
+        let p = to_int(&FieldElement(P));
+        assert_eq!(
+            p,
+            (BigInt::from(1u8) << 256) - (BigInt::from(1u8) << 32) - 977
+        );
+        // 2^256 - 1 和 p 都会被约简
+        assert_eq!(
+            FieldElement::from_bytes_be(&[0xff; 32]),
+            FieldElement::from_u64(C - 1)
+        );
+        assert!(FieldElement::from_bytes_be(&FieldElement(P).to_bytes_be()).is_zero());
+
+        let p1 = from_int(&(&p - 1u8));
+        assert_eq!(p1 + FieldElement::ONE, FieldElement::ZERO);
+        assert_eq!(FieldElement::ZERO - FieldElement::ONE, p1);
+        assert_eq!(p1 * p1, FieldElement::ONE);
+        assert_eq!(-FieldElement::ZERO, FieldElement::ZERO);
+        assert!(FieldElement::ZERO.invert().is_none());
+
+        // 和 BigInt 对比, 覆盖各种进位
+        let mut values = vec![
+            BigInt::from(0u8),
+            BigInt::from(1u8),
+            BigInt::from(C),
+            &p - 1u8,
+            &p - 2u8,
+            &p >> 1,
+            (BigInt::from(1u8) << 255) + 12345,
+            BigInt::from(u64::MAX),
+        ];
+        let mut x = BigInt::from(0x1234_5678_9abc_def0u64);
+        for _ in 0..20 {
+            x = mod_p(&x * &x * 7 + 3, &p);
+            values.push(x.clone());
+        }
+        for a in &values {
+            let fa = from_int(a);
+            assert_eq!(to_int(&fa), *a);
+            assert_eq!(to_int(&-fa), mod_p(-a, &p));
+            if !a.is_zero() {
+                let inv = fa.invert().unwrap();
+                assert_eq!(inv * fa, FieldElement::ONE);
+            }
+            for b in &values {
+                let fb = from_int(b);
+                assert_eq!(to_int(&(fa + fb)), mod_p(a + b, &p));
+                assert_eq!(to_int(&(fa - fb)), mod_p(a - b, &p));
+                assert_eq!(to_int(&(fa * fb)), mod_p(a * b, &p));
+            }
+        }
+
+        let (mut a, mut b) = (FieldElement::ONE, p1);
+        FieldElement::cswap(0, &mut a, &mut b);
+        assert_eq!((a, b), (FieldElement::ONE, p1));
+        FieldElement::cswap(1, &mut a, &mut b);
+        assert_eq!((a, b), (p1, FieldElement::ONE));
+    }
+}
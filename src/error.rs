@@ -1,9 +1,11 @@
+use crate::conv::bytes_to_hex;
 use std::fmt;
 
 #[derive(Debug)]
 pub enum DecodeHexError {
     OddLength(usize),
     InvalidChar { c: char, idx: usize },
+    OutputTooSmall { len: usize, needed: usize },
 }
 
 impl std::error::Error for DecodeHexError {} // Error trait
@@ -13,6 +15,47 @@ impl fmt::Display for DecodeHexError {
         match *self {
             Self::OddLength(n) => write!(f, "The length to decode is odd: `{}`", n),
             Self::InvalidChar { c, idx } => write!(f, "Invalid char at {}: `{}`", idx, c),
+            Self::OutputTooSmall { len, needed } => {
+                write!(
+                    f,
+                    "The output buffer is too small: `{}` (< {})",
+                    len, needed
+                )
+            }
+        }
+    }
+}
+
+/// 定长整数和字节互转时长度不对
+#[derive(Debug)]
+pub enum ConvError {
+    InvalidLength { expected: usize, found: usize },
+    TooLarge { len: usize, max: usize },
+    NotMultiple { len: usize, word: usize },
+    OutputTooSmall { len: usize, needed: usize },
+}
+
+impl std::error::Error for ConvError {} // Error trait
+
+impl fmt::Display for ConvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidLength { expected, found } => {
+                write!(f, "Invalid length: `{}` (expected {})", found, expected)
+            }
+            Self::TooLarge { len, max } => {
+                write!(f, "The value is too large: `{}` bytes (> {})", len, max)
+            }
+            Self::NotMultiple { len, word } => {
+                write!(f, "The length `{}` is not a multiple of {}", len, word)
+            }
+            Self::OutputTooSmall { len, needed } => {
+                write!(
+                    f,
+                    "The output buffer is too small: `{}` (< {})",
+                    len, needed
+                )
+            }
         }
     }
 }
@@ -21,6 +64,9 @@ impl fmt::Display for DecodeHexError {
 pub enum DecodeBase64Error {
     InvalidLength(usize),
     InvalidChar { c: char, idx: usize },
+    InvalidPadding(usize),
+    NonCanonical(usize),
+    OutputTooSmall { len: usize, needed: usize },
 }
 
 impl std::error::Error for DecodeBase64Error {} // Error trait
@@ -28,11 +74,59 @@ impl std::error::Error for DecodeBase64Error {} // Error trait
 impl fmt::Display for DecodeBase64Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Self::InvalidLength(n) if n == 0 => write!(f, "Empty string to decode"),
+            Self::InvalidLength(0) => write!(f, "Empty string to decode"),
             Self::InvalidLength(n) => {
                 write!(f, "The length to decode is invalid: `{}` (% 4 != 0)", n)
             }
             Self::InvalidChar { c, idx } => write!(f, "Invalid char at {}: `{}`", idx, c),
+            Self::InvalidPadding(idx) => write!(f, "Invalid padding at {}", idx),
+            Self::NonCanonical(idx) => {
+                write!(
+                    f,
+                    "Non-canonical encoding at {}: trailing bits are not zero",
+                    idx
+                )
+            }
+            Self::OutputTooSmall { len, needed } => {
+                write!(
+                    f,
+                    "The output buffer is too small: `{}` (< {})",
+                    len, needed
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeBase32Error {
+    InvalidLength(usize),
+    InvalidChar { c: char, idx: usize },
+    InvalidPadding(usize),
+    InvalidChecksum { expected: char, found: char },
+}
+
+impl std::error::Error for DecodeBase32Error {} // Error trait
+
+impl fmt::Display for DecodeBase32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidLength(n) => {
+                write!(
+                    f,
+                    "The length to decode is invalid: `{}` (% 8 is 1, 3 or 6)",
+                    n
+                )
+            }
+            Self::InvalidChar { c, idx } => write!(f, "Invalid char at {}: `{}`", idx, c),
+            Self::InvalidPadding(idx) => write!(f, "Invalid padding at {}", idx),
+            Self::InvalidChecksum { expected, found } => {
+                write!(
+                    f,
+                    "Invalid check symbol: `{}` (expected {})",
+                    found, expected
+                )
+            }
         }
     }
 }
@@ -40,6 +134,8 @@ impl fmt::Display for DecodeBase64Error {
 #[derive(Debug)]
 pub enum DecodeBase58Error {
     InvalidChar { c: char, idx: usize },
+    TooShort(usize),
+    InvalidChecksum { expected: [u8; 4], found: [u8; 4] },
 }
 
 impl std::error::Error for DecodeBase58Error {} // Error trait
@@ -48,10 +144,259 @@ impl fmt::Display for DecodeBase58Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidChar { c, idx } => write!(f, "Invalid char at {}: `{}`", idx, c),
+            Self::TooShort(n) => {
+                write!(f, "The decoded length is too short: `{}` (< 4)", n)
+            }
+            Self::InvalidChecksum { expected, found } => write!(
+                f,
+                "Invalid checksum: `{}` (expected {})",
+                bytes_to_hex(found),
+                bytes_to_hex(expected)
+            ),
+        }
+    }
+}
+
+/// 编码和解码共用, 编码时 hrp 和 witness program 不合法也会报错
+#[derive(Debug)]
+pub enum Bech32Error {
+    TooLong(usize),
+    TooShort(usize),
+    MixedCase,
+    MissingSeparator,
+    InvalidHrp,
+    InvalidChar { c: char, idx: usize },
+    InvalidChecksum,
+    InvalidValue(u8),
+    InvalidPadding,
+    UnexpectedHrp(String),
+    InvalidWitnessVersion(u8),
+    InvalidProgramLength(usize),
+    InvalidVariant,
+}
+
+impl std::error::Error for Bech32Error {} // Error trait
+
+impl fmt::Display for Bech32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooLong(n) => write!(f, "The string is too long: `{}` (> 90)", n),
+            Self::TooShort(n) => write!(f, "The data part is too short: `{}` (< 6)", n),
+            Self::MixedCase => write!(f, "Mixed upper and lower case"),
+            Self::MissingSeparator => write!(f, "Missing separator `1`"),
+            Self::InvalidHrp => write!(f, "Invalid human-readable part"),
+            Self::InvalidChar { c, idx } => write!(f, "Invalid char at {}: `{}`", idx, c),
+            Self::InvalidChecksum => write!(f, "Invalid checksum"),
+            Self::InvalidValue(v) => write!(f, "Invalid value: `{}`", v),
+            Self::InvalidPadding => write!(f, "Invalid padding"),
+            Self::UnexpectedHrp(hrp) => write!(f, "Unexpected human-readable part: `{}`", hrp),
+            Self::InvalidWitnessVersion(v) => write!(f, "Invalid witness version: `{}`", v),
+            Self::InvalidProgramLength(n) => {
+                write!(f, "Invalid witness program length: `{}`", n)
+            }
+            Self::InvalidVariant => write!(f, "Wrong checksum variant for the witness version"),
         }
     }
 }
 
+#[derive(Debug)]
+pub enum RsaError {
+    MessageTooLong { len: usize, max: usize },
+    KeyTooShort(usize),
+    Decryption,
+    InvalidKey,
+    Fault,
+}
+
+impl std::error::Error for RsaError {} // Error trait
+
+impl fmt::Display for RsaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::MessageTooLong { len, max } => {
+                write!(f, "The message is too long: `{}` (> {})", len, max)
+            }
+            Self::KeyTooShort(bits) => write!(f, "The key is too short: `{}` bits", bits),
+            Self::Decryption => write!(f, "Decryption error"),
+            Self::InvalidKey => write!(f, "Invalid key parameters"),
+            Self::Fault => write!(f, "The signature failed the self-check"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeKeyError {
+    UnexpectedTag { expected: u8, found: u8, idx: usize },
+    InvalidLength(usize),
+    InvalidInteger(usize),
+    InvalidBitString(usize),
+    UnexpectedEnd,
+    TrailingData(usize),
+    UnsupportedVersion(usize),
+    UnsupportedAlgorithm,
+    InvalidPem,
+    UnexpectedLabel(String),
+    Base64(DecodeBase64Error),
+    InvalidKey,
+}
+
+impl std::error::Error for DecodeKeyError {} // Error trait
+
+impl fmt::Display for DecodeKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedTag {
+                expected,
+                found,
+                idx,
+            } => write!(
+                f,
+                "Unexpected tag at {}: `0x{:02x}` (expected 0x{:02x})",
+                idx, found, expected
+            ),
+            Self::InvalidLength(idx) => write!(f, "Invalid length at {}", idx),
+            Self::InvalidInteger(idx) => write!(f, "Invalid integer at {}", idx),
+            Self::InvalidBitString(idx) => write!(f, "Invalid bit string at {}", idx),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of data"),
+            Self::TrailingData(idx) => write!(f, "Trailing data at {}", idx),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported version: `{}`", v),
+            Self::UnsupportedAlgorithm => write!(f, "Unsupported algorithm"),
+            Self::InvalidPem => write!(f, "Invalid PEM"),
+            Self::UnexpectedLabel(label) => write!(f, "Unexpected PEM label: `{}`", label),
+            Self::Base64(e) => write!(f, "Invalid PEM body: {}", e),
+            Self::InvalidKey => write!(f, "Inconsistent key parameters"),
+        }
+    }
+}
+
+impl From<DecodeBase64Error> for DecodeKeyError {
+    fn from(e: DecodeBase64Error) -> Self {
+        Self::Base64(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum AesError {
+    InvalidKeyLength(usize),
+}
+
+impl std::error::Error for AesError {} // Error trait
+
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidKeyLength(n) => write!(
+                f,
+                "Invalid key length: `{}` bytes (AES only support 16/24/32)",
+                n
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EcError {
+    ModulusNotPrime,
+    OrderNotPrime,
+    NotOnCurve,
+    InvalidOrder,
+    UnknownCurve(String),
+    Decryption,
+    InvalidPrivateKey,
+    IdTooLong(usize),
+}
+
+impl std::error::Error for EcError {} // Error trait
+
+impl fmt::Display for EcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ModulusNotPrime => write!(f, "The modulus p is not prime"),
+            Self::OrderNotPrime => write!(f, "The order n is not prime"),
+            Self::NotOnCurve => write!(f, "The point is not on the curve"),
+            Self::InvalidOrder => write!(f, "The order of the generator is not n"),
+            Self::UnknownCurve(name) => write!(f, "Unknown curve: `{}`", name),
+            Self::Decryption => write!(f, "Decryption error"),
+            Self::InvalidPrivateKey => write!(f, "The private key is out of range"),
+            Self::IdTooLong(len) => write!(f, "The user ID is too long: `{}` bytes", len),
+        }
+    }
+}
+
+/// 整个 crate 的错误, 需要把不同的错误往上传的时候用 (`?` 会自动转换)
+#[derive(Debug)]
+pub enum Error {
+    Conv(ConvError),
+    Hex(DecodeHexError),
+    Base64(DecodeBase64Error),
+    Base32(DecodeBase32Error),
+    Base58(DecodeBase58Error),
+    Bech32(Bech32Error),
+    Aes(AesError),
+    Ec(EcError),
+    Rsa(RsaError),
+    Key(DecodeKeyError),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Conv(e) => Some(e),
+            Self::Hex(e) => Some(e),
+            Self::Base64(e) => Some(e),
+            Self::Base32(e) => Some(e),
+            Self::Base58(e) => Some(e),
+            Self::Bech32(e) => Some(e),
+            Self::Aes(e) => Some(e),
+            Self::Ec(e) => Some(e),
+            Self::Rsa(e) => Some(e),
+            Self::Key(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Conv(e) => write!(f, "conv: {}", e),
+            Self::Hex(e) => write!(f, "hex: {}", e),
+            Self::Base64(e) => write!(f, "base64: {}", e),
+            Self::Base32(e) => write!(f, "base32: {}", e),
+            Self::Base58(e) => write!(f, "base58: {}", e),
+            Self::Bech32(e) => write!(f, "bech32: {}", e),
+            Self::Aes(e) => write!(f, "aes: {}", e),
+            Self::Ec(e) => write!(f, "ecc: {}", e),
+            Self::Rsa(e) => write!(f, "rsa: {}", e),
+            Self::Key(e) => write!(f, "key: {}", e),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($variant:ident($t:ty)),* $(,)?) => {
+        $(
+            impl From<$t> for Error {
+                fn from(e: $t) -> Self {
+                    Self::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    Conv(ConvError),
+    Hex(DecodeHexError),
+    Base64(DecodeBase64Error),
+    Base32(DecodeBase32Error),
+    Base58(DecodeBase58Error),
+    Bech32(Bech32Error),
+    Aes(AesError),
+    Ec(EcError),
+    Rsa(RsaError),
+    Key(DecodeKeyError),
+);
+
 #[test]
 fn test_error() {
     // hex
@@ -63,6 +408,32 @@ fn test_error() {
         DecodeHexError::InvalidChar { c: 'g', idx: 7 }.to_string(),
         "Invalid char at 7: `g`"
     );
+    assert_eq!(
+        DecodeHexError::OutputTooSmall { len: 3, needed: 4 }.to_string(),
+        "The output buffer is too small: `3` (< 4)"
+    );
+
+    // conv
+    assert_eq!(
+        ConvError::InvalidLength {
+            expected: 4,
+            found: 3
+        }
+        .to_string(),
+        "Invalid length: `3` (expected 4)"
+    );
+    assert_eq!(
+        ConvError::TooLarge { len: 33, max: 32 }.to_string(),
+        "The value is too large: `33` bytes (> 32)"
+    );
+    assert_eq!(
+        ConvError::NotMultiple { len: 10, word: 4 }.to_string(),
+        "The length `10` is not a multiple of 4"
+    );
+    assert_eq!(
+        ConvError::OutputTooSmall { len: 7, needed: 8 }.to_string(),
+        "The output buffer is too small: `7` (< 8)"
+    );
 
     // base64
     assert_eq!(
@@ -77,10 +448,159 @@ fn test_error() {
         DecodeBase64Error::InvalidChar { c: '~', idx: 7 }.to_string(),
         "Invalid char at 7: `~`"
     );
+    assert_eq!(
+        DecodeBase64Error::InvalidPadding(2).to_string(),
+        "Invalid padding at 2"
+    );
+    assert_eq!(
+        DecodeBase64Error::NonCanonical(3).to_string(),
+        "Non-canonical encoding at 3: trailing bits are not zero"
+    );
+    assert_eq!(
+        DecodeBase64Error::OutputTooSmall { len: 4, needed: 5 }.to_string(),
+        "The output buffer is too small: `4` (< 5)"
+    );
+
+    // base32
+    assert_eq!(
+        DecodeBase32Error::InvalidLength(3).to_string(),
+        "The length to decode is invalid: `3` (% 8 is 1, 3 or 6)"
+    );
+    assert_eq!(
+        DecodeBase32Error::InvalidChar { c: '1', idx: 7 }.to_string(),
+        "Invalid char at 7: `1`"
+    );
+    assert_eq!(
+        DecodeBase32Error::InvalidPadding(2).to_string(),
+        "Invalid padding at 2"
+    );
+    assert_eq!(
+        DecodeBase32Error::InvalidChecksum {
+            expected: 'J',
+            found: '5'
+        }
+        .to_string(),
+        "Invalid check symbol: `5` (expected J)"
+    );
 
     // base58
     assert_eq!(
         DecodeBase58Error::InvalidChar { c: '~', idx: 7 }.to_string(),
         "Invalid char at 7: `~`"
     );
+    assert_eq!(
+        DecodeBase58Error::TooShort(3).to_string(),
+        "The decoded length is too short: `3` (< 4)"
+    );
+    assert_eq!(
+        DecodeBase58Error::InvalidChecksum {
+            expected: [0x01, 0x02, 0x03, 0x04],
+            found: [0xaa, 0xbb, 0xcc, 0xdd]
+        }
+        .to_string(),
+        "Invalid checksum: `aabbccdd` (expected 01020304)"
+    );
+
+    // bech32
+    assert_eq!(
+        Bech32Error::TooLong(91).to_string(),
+        "The string is too long: `91` (> 90)"
+    );
+    assert_eq!(
+        Bech32Error::InvalidChar { c: 'b', idx: 2 }.to_string(),
+        "Invalid char at 2: `b`"
+    );
+    assert_eq!(
+        Bech32Error::UnexpectedHrp("tb".to_string()).to_string(),
+        "Unexpected human-readable part: `tb`"
+    );
+    assert_eq!(
+        Bech32Error::InvalidProgramLength(41).to_string(),
+        "Invalid witness program length: `41`"
+    );
+
+    // rsa
+    assert_eq!(
+        RsaError::MessageTooLong { len: 100, max: 62 }.to_string(),
+        "The message is too long: `100` (> 62)"
+    );
+    assert_eq!(
+        RsaError::KeyTooShort(256).to_string(),
+        "The key is too short: `256` bits"
+    );
+    assert_eq!(RsaError::Decryption.to_string(), "Decryption error");
+    assert_eq!(RsaError::InvalidKey.to_string(), "Invalid key parameters");
+    assert_eq!(
+        RsaError::Fault.to_string(),
+        "The signature failed the self-check"
+    );
+
+    // key
+    assert_eq!(
+        DecodeKeyError::UnexpectedTag {
+            expected: 0x30,
+            found: 0x02,
+            idx: 4
+        }
+        .to_string(),
+        "Unexpected tag at 4: `0x02` (expected 0x30)"
+    );
+    assert_eq!(
+        DecodeKeyError::InvalidKey.to_string(),
+        "Inconsistent key parameters"
+    );
+    assert_eq!(
+        DecodeKeyError::UnexpectedLabel("PUBLIC KEY".to_string()).to_string(),
+        "Unexpected PEM label: `PUBLIC KEY`"
+    );
+    assert_eq!(
+        DecodeKeyError::from(DecodeBase64Error::InvalidLength(5)).to_string(),
+        "Invalid PEM body: The length to decode is invalid: `5` (% 4 != 0)"
+    );
+
+    // aes
+    assert_eq!(
+        AesError::InvalidKeyLength(17).to_string(),
+        "Invalid key length: `17` bytes (AES only support 16/24/32)"
+    );
+
+    // ecc
+    assert_eq!(
+        EcError::NotOnCurve.to_string(),
+        "The point is not on the curve"
+    );
+    assert_eq!(
+        EcError::InvalidOrder.to_string(),
+        "The order of the generator is not n"
+    );
+    assert_eq!(
+        EcError::UnknownCurve("P-255".to_string()).to_string(),
+        "Unknown curve: `P-255`"
+    );
+    assert_eq!(EcError::Decryption.to_string(), "Decryption error");
+    assert_eq!(
+        EcError::InvalidPrivateKey.to_string(),
+        "The private key is out of range"
+    );
+    assert_eq!(
+        EcError::IdTooLong(8192).to_string(),
+        "The user ID is too long: `8192` bytes"
+    );
+
+    // crate 的错误
+    use std::error::Error as _;
+    let e = Error::from(DecodeHexError::OddLength(5));
+    assert_eq!(e.to_string(), "hex: The length to decode is odd: `5`");
+    assert_eq!(
+        e.source().unwrap().to_string(),
+        "The length to decode is odd: `5`"
+    );
+    assert!(matches!(
+        Error::from(EcError::OrderNotPrime),
+        Error::Ec(EcError::OrderNotPrime)
+    ));
+    assert_eq!(
+        Error::from(RsaError::Decryption).to_string(),
+        "rsa: Decryption error"
+    );
 }
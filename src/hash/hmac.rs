@@ -0,0 +1,90 @@
+//! HMAC (RFC 2104): H((K ^ opad) || H((K ^ ipad) || m))
+
+use super::Digest;
+
+pub struct Hmac<H: Digest> {
+    inner: H,
+    outer: H,
+}
+
+impl<H: Digest> Hmac<H> {
+    /// 比分组长的 key 先哈希一次, 短的补 0
+    pub fn new(key: &[u8]) -> Self {
+        let mut k = if key.len() > H::BLOCK_LEN {
+            H::digest(key)
+        } else {
+            key.to_vec()
+        };
+        k.resize(H::BLOCK_LEN, 0);
+
+        let mut inner = H::new();
+        inner.update(&k.iter().map(|b| b ^ 0x36).collect::<Vec<_>>());
+        let mut outer = H::new();
+        outer.update(&k.iter().map(|b| b ^ 0x5c).collect::<Vec<_>>());
+        Self { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.outer.update(&self.inner.finalize());
+        self.outer.finalize()
+    }
+
+    /// one-shot
+    pub fn mac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut h = Self::new(key);
+        h.update(data);
+        h.finalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::bytes_to_hex;
+    use crate::hash::{Sha256, Sha512};
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231 的 test case 1, 2, 6
+        let long_key = [0xaa; 131];
+        for (key, data, md256, md512) in [
+            (
+                &[0x0b; 20][..],
+                &b"Hi There"[..],
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+                 daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                 9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                &long_key,
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+                 6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+        ] {
+            assert_eq!(bytes_to_hex(&Hmac::<Sha256>::mac(key, data)), md256);
+            assert_eq!(bytes_to_hex(&Hmac::<Sha512>::mac(key, data)), md512);
+        }
+
+        // 分多次 update
+        let mut h = Hmac::<Sha256>::new(b"Jefe");
+        h.update(b"what do ya want ");
+        h.update(b"for nothing?");
+        assert_eq!(
+            h.finalize(),
+            Hmac::<Sha256>::mac(b"Jefe", b"what do ya want for nothing?")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! 哈希函数 (streaming: new -> update* -> finalize)
+
+mod sha2;
+pub use sha2::{Sha224, Sha256, Sha384, Sha512};
+
+mod ripemd;
+pub use ripemd::Ripemd160;
+
+mod sha3;
+pub use sha3::{Keccak256, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256, XofReader};
+
+mod sm3;
+pub use sm3::Sm3;
+
+mod hmac;
+pub use hmac::Hmac;
+
+pub trait Digest {
+    /// 摘要长度 (bytes)
+    const OUTPUT_LEN: usize;
+    /// 分组长度 (bytes), HMAC 会用到
+    const BLOCK_LEN: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+
+    /// one-shot
+    fn digest(data: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut h = Self::new();
+        h.update(data);
+        h.finalize()
+    }
+}
+
+/// 有标准 OID 的哈希, PKCS#1 v1.5 签名只接受这些 (Keccak256 没有)
+pub trait HashOid: Digest {
+    /// DER 编码的算法 OID (不含 tag 和 length), DigestInfo 会用到
+    const OID: &'static [u8];
+}
+
+/// 比特币的 HASH160: RIPEMD-160(SHA-256(data)), 用于地址 (公钥哈希)
+pub fn hash160(data: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(&Sha256::digest(data))
+}
+
+/// 比特币的 HASH256: SHA-256(SHA-256(data)), 用于 txid, 区块哈希和 base58check 的校验和
+pub fn hash256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(&Sha256::digest(data))
+}
+
+/// 缓存不满一个 block 的输入, 凑满了再交给 compress
+#[derive(Clone)]
+pub(crate) struct BlockBuffer<const B: usize> {
+    buf: [u8; B],
+    len: usize,
+}
+
+impl<const B: usize> BlockBuffer<B> {
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: [0; B],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8])) {
+        if self.len > 0 {
+            let k = (B - self.len).min(data.len());
+            self.buf[self.len..self.len + k].copy_from_slice(&data[..k]);
+            self.len += k;
+            data = &data[k..];
+            if self.len < B {
+                return;
+            }
+            compress(&self.buf);
+            self.len = 0;
+        }
+        let mut blocks = data.chunks_exact(B);
+        for block in &mut blocks {
+            compress(block);
+        }
+        let rest = blocks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+    }
+
+    /// MD 结构的填充: 0x80 || 0x00... || 消息长度 (bits), 补满整数个 block
+    pub(crate) fn pad(&mut self, len_bytes: &[u8], mut compress: impl FnMut(&[u8])) {
+        self.buf[self.len] = 0x80;
+        self.buf[self.len + 1..].fill(0);
+        if self.len + 1 + len_bytes.len() > B {
+            compress(&self.buf);
+            self.buf.fill(0);
+        }
+        self.buf[B - len_bytes.len()..].copy_from_slice(len_bytes);
+        compress(&self.buf);
+        self.len = 0;
+    }
+
+    /// sponge 结构的填充 (pad10*1): domain || 0x00... || 0x80
+    pub(crate) fn pad_sponge(&mut self, domain: u8, mut compress: impl FnMut(&[u8])) {
+        self.buf[self.len] = domain;
+        self.buf[self.len + 1..].fill(0);
+        self.buf[B - 1] |= 0x80;
+        compress(&self.buf);
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::{bytes_to_hex, hex_to_bytes};
+
+    #[test]
+    fn test_bitcoin_hash() {
+        // 私钥为 1 的压缩公钥, 地址 1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH
+        let pub_key =
+            hex_to_bytes("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        assert_eq!(
+            bytes_to_hex(&hash160(&pub_key)),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert_eq!(
+            bytes_to_hex(&hash256(b"hello")),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! SHA-2 (FIPS 180-4)
+
+use super::{BlockBuffer, Digest, HashOid};
+
+const SHA224_H0: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+const SHA256_H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const SHA384_H0: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+const SHA512_H0: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// SHA-224 和 SHA-256 只有初始值和输出长度不同
+#[derive(Clone)]
+struct Sha256Core {
+    state: [u32; 8],
+    buffer: BlockBuffer<64>,
+    total_len: u64, // bytes
+}
+
+impl Sha256Core {
+    fn new(h0: [u32; 8]) -> Self {
+        Self {
+            state: h0,
+            buffer: BlockBuffer::new(),
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        self.buffer
+            .update(data, |block| sha256_compress(&mut self.state, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let bit_len = self.total_len << 3;
+        self.buffer.pad(&bit_len.to_be_bytes(), |block| {
+            sha256_compress(&mut self.state, block)
+        });
+        self.state.iter().flat_map(|x| x.to_be_bytes()).collect()
+    }
+}
+
+fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// SHA-384 和 SHA-512 只有初始值和输出长度不同
+#[derive(Clone)]
+struct Sha512Core {
+    state: [u64; 8],
+    buffer: BlockBuffer<128>,
+    total_len: u128, // bytes
+}
+
+impl Sha512Core {
+    fn new(h0: [u64; 8]) -> Self {
+        Self {
+            state: h0,
+            buffer: BlockBuffer::new(),
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u128;
+        self.buffer
+            .update(data, |block| sha512_compress(&mut self.state, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        let bit_len = self.total_len << 3;
+        self.buffer.pad(&bit_len.to_be_bytes(), |block| {
+            sha512_compress(&mut self.state, block)
+        });
+        self.state.iter().flat_map(|x| x.to_be_bytes()).collect()
+    }
+}
+
+fn sha512_compress(state: &mut [u64; 8], block: &[u8]) {
+    let mut w = [0u64; 80];
+    for (i, word) in block.chunks_exact(8).enumerate() {
+        w[i] = u64::from_be_bytes(word.try_into().unwrap());
+    }
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA512_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha224(Sha256Core);
+
+#[derive(Clone)]
+pub struct Sha256(Sha256Core);
+
+#[derive(Clone)]
+pub struct Sha384(Sha512Core);
+
+#[derive(Clone)]
+pub struct Sha512(Sha512Core);
+
+impl Digest for Sha224 {
+    const OUTPUT_LEN: usize = 28;
+    const BLOCK_LEN: usize = 64;
+
+    fn new() -> Self {
+        Self(Sha256Core::new(SHA224_H0))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        let mut res = self.0.finalize();
+        res.truncate(Self::OUTPUT_LEN);
+        res
+    }
+}
+
+impl HashOid for Sha224 {
+    // 2.16.840.1.101.3.4.2.4
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x04];
+}
+
+impl Digest for Sha256 {
+    const OUTPUT_LEN: usize = 32;
+    const BLOCK_LEN: usize = 64;
+
+    fn new() -> Self {
+        Self(Sha256Core::new(SHA256_H0))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize()
+    }
+}
+
+impl HashOid for Sha256 {
+    // 2.16.840.1.101.3.4.2.1
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+}
+
+impl Digest for Sha384 {
+    const OUTPUT_LEN: usize = 48;
+    const BLOCK_LEN: usize = 128;
+
+    fn new() -> Self {
+        Self(Sha512Core::new(SHA384_H0))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        let mut res = self.0.finalize();
+        res.truncate(Self::OUTPUT_LEN);
+        res
+    }
+}
+
+impl HashOid for Sha384 {
+    // 2.16.840.1.101.3.4.2.2
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+}
+
+impl Digest for Sha512 {
+    const OUTPUT_LEN: usize = 64;
+    const BLOCK_LEN: usize = 128;
+
+    fn new() -> Self {
+        Self(Sha512Core::new(SHA512_H0))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize()
+    }
+}
+
+impl HashOid for Sha512 {
+    // 2.16.840.1.101.3.4.2.3
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::{bytes_to_hex, hex_to_bytes};
+    use openssl::hash::{hash, MessageDigest};
+
+    fn check<H: Digest>(msg_hex: &str, md: &str) {
+        assert_eq!(
+            bytes_to_hex(&H::digest(&hex_to_bytes(msg_hex).unwrap())),
+            md
+        );
+    }
+
+    /// 每次喂给 update 的长度不同, 覆盖跨 block 的情况
+    fn check_streaming<H: Digest>(md: MessageDigest) {
+        let msg: Vec<u8> = (0..600u32).map(|i| (i * 7 + 3) as u8).collect();
+        for n in [0, 1, 55, 56, 63, 64, 65, 111, 112, 127, 128, 129, 300, 600] {
+            let expected = hash(md, &msg[..n]).unwrap().to_vec();
+            assert_eq!(H::digest(&msg[..n]), expected);
+            for step in [1, 3, 64, 100] {
+                let mut h = H::new();
+                for c in msg[..n].chunks(step) {
+                    h.update(c);
+                }
+                assert_eq!(h.finalize(), expected);
+            }
+        }
+    }
+
+    // FIPS 180-2 附录以及 NIST SHAVS (SHA*ShortMsg.rsp) 的测试数据
+    const ABC: &str = "616263";
+    const MSG_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const MSG_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn test_sha224() {
+        check::<Sha224>(
+            "",
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+        );
+        check::<Sha224>(
+            ABC,
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+        );
+        check::<Sha224>(
+            "84",
+            "3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a",
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha224::digest(MSG_448)),
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+        );
+        check_streaming::<Sha224>(MessageDigest::sha224());
+    }
+
+    #[test]
+    fn test_sha256() {
+        check::<Sha256>(
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+        check::<Sha256>(
+            ABC,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        check::<Sha256>(
+            "d3",
+            "28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1",
+        );
+        check::<Sha256>(
+            "11af",
+            "5ca7133fa735326081558ac312c620eeca9970d1e70a4b95533d956f072d1f98",
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha256::digest(MSG_448)),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // 一百万个 a
+        let mut h = Sha256::new();
+        for _ in 0..1000 {
+            h.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            bytes_to_hex(&h.finalize()),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+        check_streaming::<Sha256>(MessageDigest::sha256());
+    }
+
+    #[test]
+    fn test_sha384() {
+        check::<Sha384>(ABC, "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7");
+        check::<Sha384>("c5", "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860ba432edfd86b4cb1cb8a75b46076e3b1");
+        assert_eq!(
+            bytes_to_hex(&Sha384::digest(MSG_896)),
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+        );
+        check_streaming::<Sha384>(MessageDigest::sha384());
+    }
+
+    #[test]
+    fn test_sha512() {
+        check::<Sha512>(ABC, "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+        check::<Sha512>("21", "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee23889f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a");
+        assert_eq!(
+            bytes_to_hex(&Sha512::digest(MSG_896)),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+        check_streaming::<Sha512>(MessageDigest::sha512());
+    }
+}
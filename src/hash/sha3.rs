@@ -0,0 +1,426 @@
+//! SHA-3 / SHAKE (FIPS 202), 以及以太坊用的 Keccak-256 (只有填充不同)
+
+use super::{BlockBuffer, Digest, HashOid};
+
+// 填充的第一个字节, 后面都是 pad10*1
+const DOMAIN_KECCAK: u8 = 0x01;
+const DOMAIN_SHA3: u8 = 0x06;
+const DOMAIN_SHAKE: u8 = 0x1f;
+
+const RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// rho 和 pi 合在一起: 沿着 pi 的置换顺序依次旋转
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// Keccak-f[1600], a[x + 5 * y]
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in RC {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho + pi
+        let mut last = a[1];
+        for i in 0..24 {
+            let tmp = a[PI[i]];
+            a[PI[i]] = last.rotate_left(RHO[i]);
+            last = tmp;
+        }
+
+        // chi
+        for y in 0..5 {
+            let row = [
+                a[5 * y],
+                a[5 * y + 1],
+                a[5 * y + 2],
+                a[5 * y + 3],
+                a[5 * y + 4],
+            ];
+            for x in 0..5 {
+                a[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// state 的前 rate 个字节和 block 异或 (lane 是 little endian)
+fn absorb(state: &mut [u64; 25], block: &[u8]) {
+    for (s, lane) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *s ^= u64::from_le_bytes(lane.try_into().unwrap());
+    }
+    keccak_f(state);
+}
+
+/// 各种变体只有 rate (= 1600 / 8 - 2 * 输出长度) 和填充不同
+#[derive(Clone)]
+struct KeccakCore<const RATE: usize> {
+    state: [u64; 25],
+    buffer: BlockBuffer<RATE>,
+}
+
+impl<const RATE: usize> KeccakCore<RATE> {
+    fn new() -> Self {
+        Self {
+            state: [0; 25],
+            buffer: BlockBuffer::new(),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.buffer
+            .update(data, |block| absorb(&mut self.state, block));
+    }
+
+    fn finalize_xof(mut self, domain: u8) -> XofReader {
+        self.buffer
+            .pad_sponge(domain, |block| absorb(&mut self.state, block));
+        XofReader {
+            state: self.state,
+            rate: RATE,
+            pos: 0,
+        }
+    }
+
+    fn finalize(self, domain: u8, out_len: usize) -> Vec<u8> {
+        let mut res = vec![0; out_len];
+        self.finalize_xof(domain).read(&mut res);
+        res
+    }
+}
+
+/// SHAKE 的输出, 可以无限读下去 (squeeze)
+#[derive(Clone)]
+pub struct XofReader {
+    state: [u64; 25],
+    rate: usize,
+    pos: usize, // 当前 block 已经读出的字节数
+}
+
+impl XofReader {
+    pub fn read(&mut self, out: &mut [u8]) {
+        for v in out.iter_mut() {
+            if self.pos == self.rate {
+                keccak_f(&mut self.state);
+                self.pos = 0;
+            }
+            *v = (self.state[self.pos >> 3] >> ((self.pos & 7) << 3)) as u8;
+            self.pos += 1;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha3_224(KeccakCore<144>);
+
+#[derive(Clone)]
+pub struct Sha3_256(KeccakCore<136>);
+
+#[derive(Clone)]
+pub struct Sha3_384(KeccakCore<104>);
+
+#[derive(Clone)]
+pub struct Sha3_512(KeccakCore<72>);
+
+/// 以太坊的 Keccak-256 (在 FIPS 202 定稿前的填充方式), 和 SHA3-256 的结果不同
+#[derive(Clone)]
+pub struct Keccak256(KeccakCore<136>);
+
+#[derive(Clone)]
+pub struct Shake128(KeccakCore<168>);
+
+#[derive(Clone)]
+pub struct Shake256(KeccakCore<136>);
+
+impl Digest for Sha3_224 {
+    const OUTPUT_LEN: usize = 28;
+    const BLOCK_LEN: usize = 144;
+
+    fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize(DOMAIN_SHA3, Self::OUTPUT_LEN)
+    }
+}
+
+impl HashOid for Sha3_224 {
+    // 2.16.840.1.101.3.4.2.7
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x07];
+}
+
+impl Digest for Sha3_256 {
+    const OUTPUT_LEN: usize = 32;
+    const BLOCK_LEN: usize = 136;
+
+    fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize(DOMAIN_SHA3, Self::OUTPUT_LEN)
+    }
+}
+
+impl HashOid for Sha3_256 {
+    // 2.16.840.1.101.3.4.2.8
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x08];
+}
+
+impl Digest for Sha3_384 {
+    const OUTPUT_LEN: usize = 48;
+    const BLOCK_LEN: usize = 104;
+
+    fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize(DOMAIN_SHA3, Self::OUTPUT_LEN)
+    }
+}
+
+impl HashOid for Sha3_384 {
+    // 2.16.840.1.101.3.4.2.9
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x09];
+}
+
+impl Digest for Sha3_512 {
+    const OUTPUT_LEN: usize = 64;
+    const BLOCK_LEN: usize = 72;
+
+    fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize(DOMAIN_SHA3, Self::OUTPUT_LEN)
+    }
+}
+
+impl HashOid for Sha3_512 {
+    // 2.16.840.1.101.3.4.2.10
+    const OID: &'static [u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0a];
+}
+
+// NOTE: 没有标准的 OID, 不实现 HashOid, 不能用于 PKCS#1 v1.5 签名
+impl Digest for Keccak256 {
+    const OUTPUT_LEN: usize = 32;
+    const BLOCK_LEN: usize = 136;
+
+    fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize(DOMAIN_KECCAK, Self::OUTPUT_LEN)
+    }
+}
+
+impl Shake128 {
+    pub fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    pub fn finalize_xof(self) -> XofReader {
+        self.0.finalize_xof(DOMAIN_SHAKE)
+    }
+
+    /// one-shot, 输出 out_len 个字节
+    pub fn digest(data: &[u8], out_len: usize) -> Vec<u8> {
+        let mut h = Self::new();
+        h.update(data);
+        h.0.finalize(DOMAIN_SHAKE, out_len)
+    }
+}
+
+impl Default for Shake128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shake256 {
+    pub fn new() -> Self {
+        Self(KeccakCore::new())
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    pub fn finalize_xof(self) -> XofReader {
+        self.0.finalize_xof(DOMAIN_SHAKE)
+    }
+
+    /// one-shot, 输出 out_len 个字节
+    pub fn digest(data: &[u8], out_len: usize) -> Vec<u8> {
+        let mut h = Self::new();
+        h.update(data);
+        h.0.finalize(DOMAIN_SHAKE, out_len)
+    }
+}
+
+impl Default for Shake256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::bytes_to_hex;
+    use openssl::hash::{hash, hash_xof, MessageDigest};
+
+    fn check_streaming<H: Digest>(md: MessageDigest) {
+        let msg: Vec<u8> = (0..600u32).map(|i| (i * 7 + 3) as u8).collect();
+        for n in [0, 1, 71, 72, 103, 104, 135, 136, 143, 144, 145, 300, 600] {
+            let expected = hash(md, &msg[..n]).unwrap().to_vec();
+            assert_eq!(H::digest(&msg[..n]), expected);
+            let mut h = H::new();
+            for c in msg[..n].chunks(5) {
+                h.update(c);
+            }
+            assert_eq!(h.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_sha3() {
+        assert_eq!(
+            bytes_to_hex(&Sha3_224::digest(b"")),
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7"
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha3_256::digest(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha3_256::digest(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha3_384::digest(b"abc")),
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25"
+        );
+        assert_eq!(
+            bytes_to_hex(&Sha3_512::digest(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        check_streaming::<Sha3_224>(MessageDigest::sha3_224());
+        check_streaming::<Sha3_256>(MessageDigest::sha3_256());
+        check_streaming::<Sha3_384>(MessageDigest::sha3_384());
+        check_streaming::<Sha3_512>(MessageDigest::sha3_512());
+    }
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            bytes_to_hex(&Keccak256::digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // 以太坊的函数选择器
+        assert_eq!(
+            bytes_to_hex(&Keccak256::digest(b"transfer(address,uint256)")[..4]),
+            "a9059cbb"
+        );
+    }
+
+    #[test]
+    fn test_shake() {
+        assert_eq!(
+            bytes_to_hex(&Shake128::digest(b"", 32)),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            bytes_to_hex(&Shake256::digest(b"", 64)),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+        );
+
+        // 分多次读和一次读完的结果一样 (跨越多个 block)
+        let msg: Vec<u8> = (0..500u32).map(|i| i as u8).collect();
+        for (out, md) in [
+            (Shake128::digest(&msg, 500), MessageDigest::shake_128()),
+            (Shake256::digest(&msg, 500), MessageDigest::shake_256()),
+        ] {
+            let mut expected = vec![0; 500];
+            hash_xof(md, &msg, &mut expected).unwrap();
+            assert_eq!(out, expected);
+        }
+        let mut h = Shake128::new();
+        h.update(&msg[..100]);
+        h.update(&msg[100..]);
+        let mut reader = h.finalize_xof();
+        let mut out = vec![0; 500];
+        for chunk in out.chunks_mut(33) {
+            reader.read(chunk);
+        }
+        assert_eq!(out, Shake128::digest(&msg, 500));
+    }
+}
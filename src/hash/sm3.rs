@@ -0,0 +1,135 @@
+//! SM3 (GB/T 32905-2016), 国密的哈希, SM2 签名和加密都用它
+
+use super::{BlockBuffer, Digest, HashOid};
+
+const H0: [u32; 8] = [
+    0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d, 0xb0fb0e4e,
+];
+
+// 前 16 步和后 48 步的常量
+const T0: u32 = 0x79cc4519;
+const T1: u32 = 0x7a879d8a;
+
+#[inline]
+fn p0(x: u32) -> u32 {
+    x ^ x.rotate_left(9) ^ x.rotate_left(17)
+}
+
+#[inline]
+fn p1(x: u32) -> u32 {
+    x ^ x.rotate_left(15) ^ x.rotate_left(23)
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    // 消息扩展: W[0..68], W'[j] = W[j] ^ W[j + 4]
+    let mut w = [0u32; 68];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for j in 16..68 {
+        w[j] = p1(w[j - 16] ^ w[j - 9] ^ w[j - 3].rotate_left(15))
+            ^ w[j - 13].rotate_left(7)
+            ^ w[j - 6];
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for j in 0..64 {
+        let t = if j < 16 { T0 } else { T1 };
+        let a12 = a.rotate_left(12);
+        let ss1 = a12
+            .wrapping_add(e)
+            .wrapping_add(t.rotate_left(j as u32 % 32))
+            .rotate_left(7);
+        let ss2 = ss1 ^ a12;
+        let (ff, gg) = if j < 16 {
+            (a ^ b ^ c, e ^ f ^ g)
+        } else {
+            ((a & b) | (a & c) | (b & c), (e & f) | (!e & g))
+        };
+        let tt1 = ff
+            .wrapping_add(d)
+            .wrapping_add(ss2)
+            .wrapping_add(w[j] ^ w[j + 4]);
+        let tt2 = gg.wrapping_add(h).wrapping_add(ss1).wrapping_add(w[j]);
+        d = c;
+        c = b.rotate_left(9);
+        b = a;
+        a = tt1;
+        h = g;
+        g = f.rotate_left(19);
+        f = e;
+        e = p0(tt2);
+    }
+
+    // 和 SHA-2 不同, 这里是异或回去
+    for (s, x) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s ^= x;
+    }
+}
+
+#[derive(Clone)]
+pub struct Sm3 {
+    state: [u32; 8],
+    buffer: BlockBuffer<64>,
+    total_len: u64, // bytes
+}
+
+impl Digest for Sm3 {
+    const OUTPUT_LEN: usize = 32;
+    const BLOCK_LEN: usize = 64;
+
+    fn new() -> Self {
+        Self {
+            state: H0,
+            buffer: BlockBuffer::new(),
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        self.buffer
+            .update(data, |block| compress(&mut self.state, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        // 填充和 SHA-256 一样
+        let bit_len = self.total_len << 3;
+        self.buffer.pad(&bit_len.to_be_bytes(), |block| {
+            compress(&mut self.state, block)
+        });
+        self.state.iter().flat_map(|x| x.to_be_bytes()).collect()
+    }
+}
+
+impl HashOid for Sm3 {
+    // 1.2.156.10197.1.401
+    const OID: &'static [u8] = &[0x2a, 0x81, 0x1c, 0xcf, 0x55, 0x01, 0x83, 0x11];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::bytes_to_hex;
+
+    #[test]
+    fn test_sm3() {
+        // GB/T 32905-2016 附录 A 的两个例子
+        assert_eq!(
+            bytes_to_hex(&Sm3::digest(b"abc")),
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0"
+        );
+        let m = b"abcd".repeat(16);
+        assert_eq!(
+            bytes_to_hex(&Sm3::digest(&m)),
+            "debe9ff92275b8a138604889c18e5a4d6fdb70e5387e5765293dcba39c0c5732"
+        );
+
+        // 分多次 update 结果一样
+        let mut h = Sm3::new();
+        for chunk in m.chunks(7) {
+            h.update(chunk);
+        }
+        assert_eq!(h.finalize(), Sm3::digest(&m));
+    }
+}
@@ -0,0 +1,18 @@
+pub const INVALID_VALUE: u8 = 0xFF;
+
+pub const LOWER_ENCODE: &[u8; 16] = b"0123456789abcdef";
+pub const UPPER_ENCODE: &[u8; 16] = b"0123456789ABCDEF";
+
+/// 大小写都可以
+pub const DECODE: &[u8; 256] = &make_decode_table();
+
+const fn make_decode_table() -> [u8; 256] {
+    let mut table = [INVALID_VALUE; 256];
+    let mut i = 0;
+    while i < 16 {
+        table[LOWER_ENCODE[i] as usize] = i as u8;
+        table[UPPER_ENCODE[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
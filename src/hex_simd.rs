@@ -0,0 +1,109 @@
+//! SSSE3 加速的 hex 编解码, 每次处理 16 个字节 (32 个字符)
+//! 和 base64_simd 一样只处理完整的分组, 剩下的部分和非法字符交给 conv.rs 的查表实现
+
+/// 返回 (读入的字节数, 写出的字符数)
+pub(crate) fn encode(bytes: &[u8], res: &mut [u8], table: &[u8; 16]) -> (usize, usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("ssse3") {
+            return unsafe { x86::encode_ssse3(bytes, res, table) };
+        }
+    }
+    let _ = (bytes, res, table);
+    (0, 0)
+}
+
+/// 返回 (读入的字符数, 写出的字节数), 遇到非法字符就停下
+pub(crate) fn decode(hex: &[u8], res: &mut [u8]) -> (usize, usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("ssse3") {
+            return unsafe { x86::decode_ssse3(hex, res) };
+        }
+    }
+    let _ = (hex, res);
+    (0, 0)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn encode_ssse3(
+        bytes: &[u8],
+        res: &mut [u8],
+        table: &[u8; 16],
+    ) -> (usize, usize) {
+        let lut = _mm_loadu_si128(table.as_ptr() as *const __m128i);
+        let mask = _mm_set1_epi8(0x0f);
+        let mut i = 0;
+        let mut o = 0;
+        while i + 16 <= bytes.len() && o + 32 <= res.len() {
+            let v = _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i);
+            let hi = _mm_shuffle_epi8(lut, _mm_and_si128(_mm_srli_epi16(v, 4), mask));
+            let lo = _mm_shuffle_epi8(lut, _mm_and_si128(v, mask));
+            // 高 4 位在前
+            let out = res.as_mut_ptr().add(o) as *mut __m128i;
+            _mm_storeu_si128(out, _mm_unpacklo_epi8(hi, lo));
+            _mm_storeu_si128(out.add(1), _mm_unpackhi_epi8(hi, lo));
+            i += 16;
+            o += 32;
+        }
+        (i, o)
+    }
+
+    /// lo <= v <= hi 的位置为 0xff
+    /// ascii 都小于 0x80, 有符号比较就够了, >= 0x80 的是负数, 不在范围内
+    #[inline]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn in_range(v: __m128i, lo: u8, hi: u8) -> __m128i {
+        _mm_and_si128(
+            _mm_cmpgt_epi8(v, _mm_set1_epi8(lo as i8 - 1)),
+            _mm_cmpgt_epi8(_mm_set1_epi8(hi as i8 + 1), v),
+        )
+    }
+
+    /// 16 个字符转成 4 bit 的值, 有非法字符返回 None
+    #[inline]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn nibbles(c: __m128i) -> Option<__m128i> {
+        let is_digit = in_range(c, b'0', b'9');
+        let lower = _mm_or_si128(c, _mm_set1_epi8(0x20));
+        let is_alpha = in_range(lower, b'a', b'f');
+        if _mm_movemask_epi8(_mm_or_si128(is_digit, is_alpha)) != 0xffff {
+            return None;
+        }
+        let digit = _mm_sub_epi8(c, _mm_set1_epi8(b'0' as i8));
+        let alpha = _mm_sub_epi8(lower, _mm_set1_epi8(b'a' as i8 - 10));
+        Some(_mm_or_si128(
+            _mm_and_si128(is_digit, digit),
+            _mm_and_si128(is_alpha, alpha),
+        ))
+    }
+
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn decode_ssse3(hex: &[u8], res: &mut [u8]) -> (usize, usize) {
+        // 相邻两个值合成一个字节: hi * 16 + lo
+        let weights = _mm_set1_epi16(0x0110);
+        let mut i = 0;
+        let mut o = 0;
+        while i + 32 <= hex.len() && o + 16 <= res.len() {
+            let a = _mm_loadu_si128(hex.as_ptr().add(i) as *const __m128i);
+            let b = _mm_loadu_si128(hex.as_ptr().add(i + 16) as *const __m128i);
+            let (a, b) = match (nibbles(a), nibbles(b)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => break,
+            };
+            let a = _mm_maddubs_epi16(a, weights);
+            let b = _mm_maddubs_epi16(b, weights);
+            _mm_storeu_si128(
+                res.as_mut_ptr().add(o) as *mut __m128i,
+                _mm_packus_epi16(a, b),
+            );
+            i += 32;
+            o += 16;
+        }
+        (i, o)
+    }
+}
@@ -5,10 +5,32 @@ pub use aes::AES;
 
 pub mod ecc;
 pub use ecc::Ec;
+mod ecc_arith;
+mod ecc_field;
+
+pub mod rsa;
+pub mod sm2;
+pub use rsa::{RsaPrivateKey, RsaPublicKey};
+
+pub mod hash;
+
+mod der;
+pub mod pem;
 
 pub mod conv;
+mod hex_const;
+mod hex_simd;
 
 pub mod error;
+pub use error::Error;
 
 pub mod base64;
 mod base64_const;
+mod base64_simd;
+
+pub mod base32;
+mod base32_const;
+pub mod base58;
+mod base58_const;
+pub mod bech32;
+mod bech32_const;
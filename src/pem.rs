@@ -0,0 +1,68 @@
+//! PEM (RFC 7468): -----BEGIN label----- + base64 (每行 64 个字符) + -----END label-----
+
+use crate::base64;
+use crate::error::DecodeKeyError;
+
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let body = base64::encode_pem(der);
+    let mut res = String::with_capacity(body.len() + label.len() * 2 + 40);
+    res.push_str("-----BEGIN ");
+    res.push_str(label);
+    res.push_str("-----\n");
+    if !body.is_empty() {
+        // base64 的输出一定是 ascii
+        res.push_str(std::str::from_utf8(&body).unwrap());
+        res.push('\n');
+    }
+    res.push_str("-----END ");
+    res.push_str(label);
+    res.push_str("-----\n");
+    res
+}
+
+/// 返回 (label, der), 只解析第一个 block, 忽略前后的其他内容
+pub fn decode(pem: &str) -> Result<(String, Vec<u8>), DecodeKeyError> {
+    let begin = pem.find("-----BEGIN ").ok_or(DecodeKeyError::InvalidPem)?;
+    let rest = &pem[begin + 11..];
+    let label_end = rest.find("-----").ok_or(DecodeKeyError::InvalidPem)?;
+    let label = &rest[..label_end];
+    let rest = &rest[label_end + 5..];
+
+    let end_line = format!("-----END {}-----", label);
+    let end = rest.find(&end_line).ok_or(DecodeKeyError::InvalidPem)?;
+    let body = base64::decode_lenient(&rest.as_bytes()[..end])?;
+    Ok((label.to_string(), body))
+}
+
+/// 和 decode 一样, 但要求 label 一致
+pub fn decode_label(pem: &str, label: &str) -> Result<Vec<u8>, DecodeKeyError> {
+    match decode(pem)? {
+        (found, der) if found == label => Ok(der),
+        (found, _) => Err(DecodeKeyError::UnexpectedLabel(found)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pem() {
+        let der: Vec<u8> = (0..100).collect();
+        let pem = encode("TEST", &der);
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "-----BEGIN TEST-----");
+        assert_eq!(lines[1].len(), 64);
+        assert_eq!(lines[4], "-----END TEST-----");
+
+        assert_eq!(decode(&pem).unwrap(), ("TEST".to_string(), der.clone()));
+        let crlf = format!("junk\r\n{}\r\nmore junk", pem.replace('\n', "\r\n"));
+        assert_eq!(decode_label(&crlf, "TEST").unwrap(), der);
+        assert!(matches!(
+            decode_label(&pem, "PRIVATE KEY"),
+            Err(DecodeKeyError::UnexpectedLabel(_))
+        ));
+        assert!(decode("-----BEGIN TEST-----\nAAAA\n-----END OTHER-----").is_err());
+    }
+}
@@ -0,0 +1,869 @@
+//! RSA (PKCS #1 v2.2, RFC 8017)
+//! 加密: OAEP, 签名: PSS 和 PKCS#1 v1.5
+
+use crate::conv::biguint_to_be;
+use crate::der::{self, DerReader, TAG_OCTET_STRING, TAG_OID};
+use crate::ecc::calc_inv;
+use crate::error::{DecodeKeyError, RsaError};
+use crate::hash::{Digest, HashOid};
+use crate::pem;
+use num_bigint::{BigUint, ToBigInt};
+use num_integer::Integer;
+use num_primes::{Generator, Verification};
+use num_traits::{One, ToPrimitive, Zero};
+
+const DEFAULT_EXP: u32 = 65537;
+
+// 1.2.840.113549.1.1.1
+const RSA_ENCRYPTION_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RsaPublicKey {
+    n: BigUint,
+    e: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RsaPrivateKey {
+    n: BigUint,
+    e: BigUint,
+    d: BigUint,
+    p: BigUint,
+    q: BigUint,
+    // CRT 加速用: dp = d mod (p - 1), dq = d mod (q - 1), qinv = q^{-1} mod p
+    dp: BigUint,
+    dq: BigUint,
+    qinv: BigUint,
+}
+
+impl RsaPublicKey {
+    pub fn new(n: BigUint, e: BigUint) -> Self {
+        Self { n, e }
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    /// 模长 (bytes)
+    pub fn size(&self) -> usize {
+        (self.n.bits() + 7) >> 3
+    }
+
+    /// RSAEP / RSAVP1
+    fn raw_encrypt(&self, m: &BigUint) -> BigUint {
+        m.modpow(&self.e, &self.n)
+    }
+
+    pub fn encrypt_oaep<H: Digest>(&self, msg: &[u8], label: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let h_len = H::OUTPUT_LEN;
+        if k < (h_len << 1) + 2 {
+            return Err(RsaError::KeyTooShort(self.n.bits()));
+        }
+        let max = k - (h_len << 1) - 2;
+        if msg.len() > max {
+            return Err(RsaError::MessageTooLong {
+                len: msg.len(),
+                max,
+            });
+        }
+
+        // EM = 0x00 || maskedSeed || maskedDB, DB = lHash || PS || 0x01 || M
+        let mut em = vec![0; k];
+        let (seed, db) = em[1..].split_at_mut(h_len);
+        let db_len = db.len();
+        db[..h_len].copy_from_slice(&H::digest(label));
+        db[db_len - msg.len() - 1] = 0x01;
+        db[db_len - msg.len()..].copy_from_slice(msg);
+        seed.copy_from_slice(&random_bytes(h_len));
+        mgf1_xor::<H>(db, seed);
+        mgf1_xor::<H>(seed, db);
+
+        let c = self.raw_encrypt(&BigUint::from_bytes_be(&em));
+        Ok(i2osp(&c, k))
+    }
+
+    pub fn verify_pkcs1v15<H: HashOid>(&self, msg: &[u8], sig: &[u8]) -> bool {
+        let k = self.size();
+        if sig.len() != k {
+            return false;
+        }
+        let s = BigUint::from_bytes_be(sig);
+        if s >= self.n {
+            return false;
+        }
+        match pkcs1v15_encode::<H>(msg, k) {
+            Ok(em) => i2osp(&self.raw_encrypt(&s), k) == em,
+            Err(_) => false,
+        }
+    }
+
+    pub fn verify_pss<H: Digest>(&self, msg: &[u8], sig: &[u8], salt_len: usize) -> bool {
+        let k = self.size();
+        if sig.len() != k {
+            return false;
+        }
+        let s = BigUint::from_bytes_be(sig);
+        if s >= self.n {
+            return false;
+        }
+        let em_bits = match self.n.bits().checked_sub(1) {
+            Some(em_bits) => em_bits,
+            None => return false,
+        };
+        let em_len = (em_bits + 7) >> 3;
+        let m = self.raw_encrypt(&s);
+        if m.bits() > em_len << 3 {
+            return false;
+        }
+        pss_verify::<H>(msg, &mut i2osp(&m, em_len), em_bits, salt_len)
+    }
+}
+
+impl RsaPrivateKey {
+    /// 生成 bits 位的密钥, e = 65537
+    pub fn new(bits: usize) -> Result<Self, RsaError> {
+        Self::with_exp(bits, DEFAULT_EXP)
+    }
+
+    /// bits 至少 512, e 是 >= 3 的奇数
+    pub fn with_exp(bits: usize, e: u32) -> Result<Self, RsaError> {
+        if bits < 512 {
+            return Err(RsaError::KeyTooShort(bits));
+        }
+        if e < 3 || e & 1 == 0 {
+            return Err(RsaError::InvalidKey);
+        }
+        let e = BigUint::from(e);
+        let p_bits = (bits + 1) >> 1;
+        loop {
+            let p = gen_prime(p_bits, &e);
+            let q = gen_prime(bits - p_bits, &e);
+            if p != q {
+                // 最高两位都置 1 了, n 一定是 bits 位
+                return Self::from_primes(p, q, e);
+            }
+        }
+    }
+
+    /// 由 p, q, e 计算出 d 以及 CRT 参数; gcd(e, phi(n)) != 1 时 d 不存在
+    pub fn from_primes(p: BigUint, q: BigUint, e: BigUint) -> Result<Self, RsaError> {
+        let one = BigUint::one();
+        if p <= one || q <= one {
+            return Err(RsaError::InvalidKey);
+        }
+        let phi = (&p - &one) * (&q - &one);
+        let d = calc_inv(e.to_bigint().unwrap(), &phi.to_bigint().unwrap())
+            .ok_or(RsaError::InvalidKey)?
+            .to_biguint()
+            .unwrap();
+        Self::from_components(&p * &q, e, d, p, q)
+    }
+
+    /// 计算 CRT 参数, 检查和 `from_pkcs1_der` 一样
+    pub fn from_components(
+        n: BigUint,
+        e: BigUint,
+        d: BigUint,
+        p: BigUint,
+        q: BigUint,
+    ) -> Result<Self, RsaError> {
+        let one = BigUint::one();
+        if p <= one || q <= one {
+            return Err(RsaError::InvalidKey);
+        }
+        let dp = &d % (&p - &one);
+        let dq = &d % (&q - &one);
+        let qinv = calc_inv(q.to_bigint().unwrap(), &p.to_bigint().unwrap())
+            .ok_or(RsaError::InvalidKey)?
+            .to_biguint()
+            .unwrap();
+        let key = Self {
+            n,
+            e,
+            d,
+            p,
+            q,
+            dp,
+            dq,
+            qinv,
+        };
+        if !key.is_consistent() {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(key)
+    }
+
+    /// 导入的参数要互相一致: n > 1, 1 < e < n, p * q = n, 0 < d < n,
+    /// dp = d mod (p - 1), dq = d mod (q - 1), e * dp ≡ 1 (mod p - 1), e * dq ≡ 1 (mod q - 1),
+    /// qinv = q^{-1} mod p
+    fn is_consistent(&self) -> bool {
+        let one = BigUint::one();
+        if check_public(&self.n, &self.e).is_err() || self.p <= one || self.q <= one {
+            return false;
+        }
+        let (p1, q1) = (&self.p - 1u8, &self.q - 1u8);
+        &self.p * &self.q == self.n
+            && !self.d.is_zero()
+            && self.d < self.n
+            && self.dp == &self.d % &p1
+            && self.dq == &self.d % &q1
+            && (&self.e * &self.dp) % &p1 == one
+            && (&self.e * &self.dq) % &q1 == one
+            && self.qinv < self.p
+            && (&self.qinv * &self.q) % &self.p == one
+    }
+
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        RsaPublicKey::new(self.n.clone(), self.e.clone())
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigUint {
+        &self.e
+    }
+
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+
+    pub fn primes(&self) -> (&BigUint, &BigUint) {
+        (&self.p, &self.q)
+    }
+
+    /// CRT 参数 (dp, dq, qinv)
+    pub fn crt_params(&self) -> (&BigUint, &BigUint, &BigUint) {
+        (&self.dp, &self.dq, &self.qinv)
+    }
+
+    /// 模长 (bytes)
+    pub fn size(&self) -> usize {
+        (self.n.bits() + 7) >> 3
+    }
+
+    /// RSADP / RSASP1. modpow 不是常数时间的, 先做 blinding: c' = c * r^e,
+    /// 解出 m' = m * r 再乘 r^-1, 私钥运算的输入和攻击者选的 c 无关
+    fn raw_decrypt(&self, c: &BigUint) -> BigUint {
+        let n = self.n.to_bigint().unwrap();
+        let (r, r_inv) = loop {
+            let r = Generator::new_uint(self.n.bits()) % &self.n;
+            if let Some(r_inv) = calc_inv(r.to_bigint().unwrap(), &n) {
+                break (r, r_inv.to_biguint().unwrap());
+            }
+        };
+        let c = (c * r.modpow(&self.e, &self.n)) % &self.n;
+        (self.raw_decrypt_crt(&c) * r_inv) % &self.n
+    }
+
+    /// 用 CRT 加速 (约 4 倍)
+    fn raw_decrypt_crt(&self, c: &BigUint) -> BigUint {
+        let m1 = c.modpow(&self.dp, &self.p);
+        let m2 = c.modpow(&self.dq, &self.q);
+        // h = qinv * (m1 - m2) mod p, 先加 p 避免出现负数
+        let h = (&self.qinv * (m1 + &self.p - &m2 % &self.p)) % &self.p;
+        m2 + h * &self.q
+    }
+
+    /// RSASP1, 返回之前检查 s^e ≡ m: CRT 的一半算错的话, gcd(s^e - m, n) 就是 p 或 q
+    fn raw_sign(&self, m: &BigUint) -> Result<BigUint, RsaError> {
+        let s = self.raw_decrypt(m);
+        if s.modpow(&self.e, &self.n) != *m {
+            return Err(RsaError::Fault);
+        }
+        Ok(s)
+    }
+
+    pub fn decrypt_oaep<H: Digest>(
+        &self,
+        cipher: &[u8],
+        label: &[u8],
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let h_len = H::OUTPUT_LEN;
+        if k < (h_len << 1) + 2 {
+            return Err(RsaError::KeyTooShort(self.n.bits()));
+        }
+        if cipher.len() != k {
+            return Err(RsaError::Decryption);
+        }
+        let c = BigUint::from_bytes_be(cipher);
+        if c >= self.n {
+            return Err(RsaError::Decryption);
+        }
+
+        let mut em = i2osp(&self.raw_decrypt(&c), k);
+        let (y, rest) = em.split_first_mut().unwrap();
+        let (seed, db) = rest.split_at_mut(h_len);
+        mgf1_xor::<H>(seed, db);
+        mgf1_xor::<H>(db, seed);
+
+        // NOTE: 不区分具体的错误原因, 避免 Manger 攻击
+        let mut ok = *y == 0 && db[..h_len] == H::digest(label)[..];
+        let mut idx = 0;
+        for (i, &v) in db.iter().enumerate().skip(h_len) {
+            if idx == 0 {
+                match v {
+                    0x00 => (),
+                    0x01 => idx = i + 1,
+                    _ => ok = false,
+                }
+            }
+        }
+        if !ok || idx == 0 {
+            return Err(RsaError::Decryption);
+        }
+        Ok(db[idx..].to_vec())
+    }
+
+    pub fn sign_pkcs1v15<H: HashOid>(&self, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let em = pkcs1v15_encode::<H>(msg, k)?;
+        Ok(i2osp(&self.raw_sign(&BigUint::from_bytes_be(&em))?, k))
+    }
+
+    pub fn sign_pss<H: Digest>(&self, msg: &[u8], salt_len: usize) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let em_bits = self.n.bits().saturating_sub(1);
+        // 先检查长度再生成 salt, 太大的 salt_len 不能先去分配内存
+        if !pss_fits::<H>((em_bits + 7) >> 3, salt_len) {
+            return Err(RsaError::KeyTooShort(self.n.bits()));
+        }
+        let em = pss_encode::<H>(msg, em_bits, &random_bytes(salt_len))
+            .ok_or(RsaError::KeyTooShort(self.n.bits()))?;
+        Ok(i2osp(&self.raw_sign(&BigUint::from_bytes_be(&em))?, k))
+    }
+}
+
+/// 密钥格式:
+/// + PKCS#1: RSAPublicKey / RSAPrivateKey
+/// + SPKI (X.509 SubjectPublicKeyInfo) / PKCS#8: 外面再包一层 AlgorithmIdentifier
+impl RsaPublicKey {
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        der::sequence(&[der::uint(&self.n), der::uint(&self.e)])
+    }
+
+    pub fn from_pkcs1_der(bytes: &[u8]) -> Result<Self, DecodeKeyError> {
+        let mut reader = DerReader::new(bytes);
+        let mut seq = reader.read_sequence()?;
+        reader.finish()?;
+        let n = seq.read_uint()?;
+        let e = seq.read_uint()?;
+        seq.finish()?;
+        check_public(&n, &e)?;
+        Ok(Self::new(n, e))
+    }
+
+    pub fn to_public_key_der(&self) -> Vec<u8> {
+        der::sequence(&[alg_id(), der::bit_string(&self.to_pkcs1_der())])
+    }
+
+    pub fn from_public_key_der(bytes: &[u8]) -> Result<Self, DecodeKeyError> {
+        let mut reader = DerReader::new(bytes);
+        let mut seq = reader.read_sequence()?;
+        reader.finish()?;
+        read_alg_id(&mut seq)?;
+        let key = seq.read_bit_string()?;
+        seq.finish()?;
+        Self::from_pkcs1_der(key)
+    }
+
+    pub fn to_pkcs1_pem(&self) -> String {
+        pem::encode("RSA PUBLIC KEY", &self.to_pkcs1_der())
+    }
+
+    pub fn from_pkcs1_pem(s: &str) -> Result<Self, DecodeKeyError> {
+        Self::from_pkcs1_der(&pem::decode_label(s, "RSA PUBLIC KEY")?)
+    }
+
+    pub fn to_public_key_pem(&self) -> String {
+        pem::encode("PUBLIC KEY", &self.to_public_key_der())
+    }
+
+    pub fn from_public_key_pem(s: &str) -> Result<Self, DecodeKeyError> {
+        Self::from_public_key_der(&pem::decode_label(s, "PUBLIC KEY")?)
+    }
+}
+
+impl RsaPrivateKey {
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        der::sequence(&[
+            der::uint(&BigUint::from(0u8)), // version: two-prime
+            der::uint(&self.n),
+            der::uint(&self.e),
+            der::uint(&self.d),
+            der::uint(&self.p),
+            der::uint(&self.q),
+            der::uint(&self.dp),
+            der::uint(&self.dq),
+            der::uint(&self.qinv),
+        ])
+    }
+
+    pub fn from_pkcs1_der(bytes: &[u8]) -> Result<Self, DecodeKeyError> {
+        let mut reader = DerReader::new(bytes);
+        let mut seq = reader.read_sequence()?;
+        reader.finish()?;
+        read_version(&mut seq)?;
+        let mut nums = Vec::with_capacity(8);
+        for _ in 0..8 {
+            nums.push(seq.read_uint()?);
+        }
+        seq.finish()?;
+        let [n, e, d, p, q, dp, dq, qinv]: [BigUint; 8] = nums.try_into().unwrap();
+        let key = Self {
+            n,
+            e,
+            d,
+            p,
+            q,
+            dp,
+            dq,
+            qinv,
+        };
+        if !key.is_consistent() {
+            return Err(DecodeKeyError::InvalidKey);
+        }
+        Ok(key)
+    }
+
+    pub fn to_pkcs8_der(&self) -> Vec<u8> {
+        der::sequence(&[
+            der::uint(&BigUint::from(0u8)),
+            alg_id(),
+            der::octet_string(&self.to_pkcs1_der()),
+        ])
+    }
+
+    pub fn from_pkcs8_der(bytes: &[u8]) -> Result<Self, DecodeKeyError> {
+        let mut reader = DerReader::new(bytes);
+        let mut seq = reader.read_sequence()?;
+        reader.finish()?;
+        read_version(&mut seq)?;
+        read_alg_id(&mut seq)?;
+        let key = seq.read(TAG_OCTET_STRING)?;
+        // 后面可能还有 [0] attributes, 忽略
+        Self::from_pkcs1_der(key)
+    }
+
+    pub fn to_pkcs1_pem(&self) -> String {
+        pem::encode("RSA PRIVATE KEY", &self.to_pkcs1_der())
+    }
+
+    pub fn from_pkcs1_pem(s: &str) -> Result<Self, DecodeKeyError> {
+        Self::from_pkcs1_der(&pem::decode_label(s, "RSA PRIVATE KEY")?)
+    }
+
+    pub fn to_pkcs8_pem(&self) -> String {
+        pem::encode("PRIVATE KEY", &self.to_pkcs8_der())
+    }
+
+    pub fn from_pkcs8_pem(s: &str) -> Result<Self, DecodeKeyError> {
+        Self::from_pkcs8_der(&pem::decode_label(s, "PRIVATE KEY")?)
+    }
+}
+
+/// 导入的参数至少要能用: n > 1, 1 < e < n
+fn check_public(n: &BigUint, e: &BigUint) -> Result<(), DecodeKeyError> {
+    let one = BigUint::one();
+    if *n > one && *e > one && e < n {
+        Ok(())
+    } else {
+        Err(DecodeKeyError::InvalidKey)
+    }
+}
+
+/// AlgorithmIdentifier ::= SEQUENCE { rsaEncryption, NULL }
+fn alg_id() -> Vec<u8> {
+    der::sequence(&[der::oid(RSA_ENCRYPTION_OID), der::null()])
+}
+
+fn read_alg_id(reader: &mut DerReader) -> Result<(), DecodeKeyError> {
+    let mut seq = reader.read_sequence()?;
+    if seq.read(TAG_OID)? != RSA_ENCRYPTION_OID {
+        return Err(DecodeKeyError::UnsupportedAlgorithm);
+    }
+    // parameters 必须是 NULL, 但有些实现会省略
+    if !seq.is_empty() {
+        seq.read_null()?;
+    }
+    seq.finish()
+}
+
+/// 只支持 version 0 (不支持 multi-prime)
+fn read_version(reader: &mut DerReader) -> Result<(), DecodeKeyError> {
+    match reader.read_uint()? {
+        v if v.is_zero() => Ok(()),
+        v => Err(DecodeKeyError::UnsupportedVersion(
+            v.to_usize().unwrap_or(usize::MAX),
+        )),
+    }
+}
+
+/// 生成 bits 位的素数 p, 最高两位置 1, 且 gcd(e, p - 1) = 1
+fn gen_prime(bits: usize, e: &BigUint) -> BigUint {
+    let one = BigUint::one();
+    let mask = (&one << (bits - 1)) | (&one << (bits - 2)) | &one;
+    loop {
+        let p = Generator::new_uint(bits) | &mask;
+        if (&p - &one).gcd(e).is_one() && Verification::is_prime(&p) {
+            return p;
+        }
+    }
+}
+
+fn random_bytes(n: usize) -> Vec<u8> {
+    i2osp(&Generator::new_uint(n << 3), n)
+}
+
+/// integer to octet string (big endian, 左侧补零), 调用的地方都保证了 x < 256^len
+fn i2osp(x: &BigUint, len: usize) -> Vec<u8> {
+    biguint_to_be(x, len).unwrap()
+}
+
+/// out ^= MGF1(seed, out.len())
+fn mgf1_xor<H: Digest>(out: &mut [u8], seed: &[u8]) {
+    for (counter, chunk) in out.chunks_mut(H::OUTPUT_LEN).enumerate() {
+        let mut h = H::new();
+        h.update(seed);
+        h.update(&(counter as u32).to_be_bytes());
+        for (o, m) in chunk.iter_mut().zip(h.finalize()) {
+            *o ^= m;
+        }
+    }
+}
+
+/// EMSA-PKCS1-v1_5: 0x00 || 0x01 || PS (0xff) || 0x00 || DigestInfo
+fn pkcs1v15_encode<H: HashOid>(msg: &[u8], em_len: usize) -> Result<Vec<u8>, RsaError> {
+    // DigestInfo ::= SEQUENCE { SEQUENCE { OID, NULL }, OCTET STRING }
+    let t = der::sequence(&[
+        der::sequence(&[der::oid(H::OID), der::null()]),
+        der::octet_string(&H::digest(msg)),
+    ]);
+
+    if em_len < t.len() + 11 {
+        return Err(RsaError::KeyTooShort(em_len << 3));
+    }
+    let mut em = vec![0xff; em_len];
+    em[0] = 0x00;
+    em[1] = 0x01;
+    em[em_len - t.len() - 1] = 0x00;
+    em[em_len - t.len()..].copy_from_slice(&t);
+    Ok(em)
+}
+
+/// em_len >= h_len + salt_len + 2, salt_len 是调用方给的, 加法可能溢出
+fn pss_fits<H: Digest>(em_len: usize, salt_len: usize) -> bool {
+    H::OUTPUT_LEN
+        .checked_add(salt_len)
+        .and_then(|x| x.checked_add(2))
+        .is_some_and(|x| em_len >= x)
+}
+
+/// EMSA-PSS-ENCODE: maskedDB || H || 0xbc, DB = PS || 0x01 || salt
+fn pss_encode<H: Digest>(msg: &[u8], em_bits: usize, salt: &[u8]) -> Option<Vec<u8>> {
+    let h_len = H::OUTPUT_LEN;
+    let em_len = (em_bits + 7) >> 3;
+    if !pss_fits::<H>(em_len, salt.len()) {
+        return None;
+    }
+
+    let mut em = vec![0; em_len];
+    let (db, rest) = em.split_at_mut(em_len - h_len - 1);
+    rest[..h_len].copy_from_slice(&pss_hash::<H>(msg, salt));
+    rest[h_len] = 0xbc;
+    let db_len = db.len();
+    db[db_len - salt.len() - 1] = 0x01;
+    db[db_len - salt.len()..].copy_from_slice(salt);
+    mgf1_xor::<H>(db, &rest[..h_len]);
+    db[0] &= 0xff >> ((em_len << 3) - em_bits);
+    Some(em)
+}
+
+fn pss_verify<H: Digest>(msg: &[u8], em: &mut [u8], em_bits: usize, salt_len: usize) -> bool {
+    let h_len = H::OUTPUT_LEN;
+    let em_len = em.len();
+    if !pss_fits::<H>(em_len, salt_len) || em[em_len - 1] != 0xbc {
+        return false;
+    }
+    let top_mask = 0xff >> ((em_len << 3) - em_bits);
+    if em[0] & !top_mask != 0 {
+        return false;
+    }
+
+    let (db, rest) = em.split_at_mut(em_len - h_len - 1);
+    let h = &rest[..h_len];
+    mgf1_xor::<H>(db, h);
+    db[0] &= top_mask;
+    let ps_len = db.len() - salt_len - 1;
+    if db[..ps_len].iter().any(|&v| v != 0) || db[ps_len] != 0x01 {
+        return false;
+    }
+    pss_hash::<H>(msg, &db[ps_len + 1..]) == h
+}
+
+/// H(0x00 * 8 || mHash || salt)
+fn pss_hash<H: Digest>(msg: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut h = H::new();
+    h.update(&[0; 8]);
+    h.update(&H::digest(msg));
+    h.update(salt);
+    h.finalize()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::Sha256;
+    use openssl::bn::BigNum;
+    use openssl::encrypt::Encrypter;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::{PKey, Private};
+    use openssl::rsa::{Padding, Rsa};
+    use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+
+    fn to_openssl(key: &RsaPrivateKey) -> PKey<Private> {
+        let bn = |x: &BigUint| BigNum::from_slice(&x.to_bytes_be()).unwrap();
+        let rsa = Rsa::from_private_components(
+            bn(&key.n),
+            bn(&key.e),
+            bn(&key.d),
+            bn(&key.p),
+            bn(&key.q),
+            bn(&key.dp),
+            bn(&key.dq),
+            bn(&key.qinv),
+        )
+        .unwrap();
+        PKey::from_rsa(rsa).unwrap()
+    }
+
+    #[test]
+    fn test_key_gen() {
+        let key = RsaPrivateKey::new(1024).unwrap();
+        assert_eq!(key.n.bits(), 1024);
+        assert_eq!(key.size(), 128);
+        assert!(to_openssl(&key).rsa().unwrap().check_key().unwrap());
+
+        let m = BigUint::from(123456789u32);
+        let c = key.to_public_key().raw_encrypt(&m);
+        assert_eq!(key.raw_decrypt(&c), m);
+        assert_eq!(c.modpow(&key.d, &key.n), m);
+
+        // 参数不对时报错而不是 panic
+        let u = |x: u32| BigUint::from(x);
+        assert!(matches!(
+            RsaPrivateKey::new(256),
+            Err(RsaError::KeyTooShort(256))
+        ));
+        for e in [0, 1, 2, 65536] {
+            assert!(RsaPrivateKey::with_exp(1024, e).is_err());
+        }
+        let key = RsaPrivateKey::from_primes(u(61), u(53), u(17)).unwrap();
+        assert_eq!(key.crt_params(), (&u(53), &u(49), &u(38)));
+        // gcd(3, 60) = 3, d 不存在
+        assert!(RsaPrivateKey::from_primes(u(7), u(11), u(3)).is_err());
+        assert!(RsaPrivateKey::from_primes(u(61), u(61), u(17)).is_err());
+        assert!(RsaPrivateKey::from_primes(u(1), u(53), u(17)).is_err());
+        assert!(RsaPrivateKey::from_components(u(3233), u(17), u(413), u(61), u(53)).is_ok());
+        for (n, e, d, p, q) in [
+            (3234, 17, 413, 61, 53),
+            (3233, 17, 414, 61, 53),
+            (3233, 3, 413, 61, 53),
+            (3233, 17, 413, 53, 61 * 53),
+            (3233, 17, 413, 0, 53),
+            (3233, 17, 0, 61, 53),
+        ] {
+            assert!(matches!(
+                RsaPrivateKey::from_components(u(n), u(e), u(d), u(p), u(q)),
+                Err(RsaError::InvalidKey)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_oaep() {
+        let key = RsaPrivateKey::new(1024).unwrap();
+        let pub_key = key.to_public_key();
+        let msg = b"hello world";
+
+        let c = pub_key.encrypt_oaep::<Sha256>(msg, b"").unwrap();
+        assert_eq!(key.decrypt_oaep::<Sha256>(&c, b"").unwrap(), msg);
+        assert!(key.decrypt_oaep::<Sha256>(&c, b"label").is_err());
+
+        let max = key.size() - 2 * 32 - 2;
+        assert!(pub_key.encrypt_oaep::<Sha256>(&vec![1; max], b"").is_ok());
+        assert!(pub_key
+            .encrypt_oaep::<Sha256>(&vec![1; max + 1], b"")
+            .is_err());
+
+        // openssl 加密, 我们解密
+        let pkey = to_openssl(&key);
+        let mut encrypter = Encrypter::new(&pkey).unwrap();
+        encrypter.set_rsa_padding(Padding::PKCS1_OAEP).unwrap();
+        encrypter.set_rsa_oaep_md(MessageDigest::sha256()).unwrap();
+        encrypter.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        let mut c = vec![0; encrypter.encrypt_len(msg).unwrap()];
+        let n = encrypter.encrypt(msg, &mut c).unwrap();
+        assert_eq!(key.decrypt_oaep::<Sha256>(&c[..n], b"").unwrap(), msg);
+    }
+
+    #[test]
+    fn test_pkcs1v15() {
+        let key = RsaPrivateKey::new(1024).unwrap();
+        let pub_key = key.to_public_key();
+        let msg = b"hello world";
+        let sig = key.sign_pkcs1v15::<Sha256>(msg).unwrap();
+        assert!(pub_key.verify_pkcs1v15::<Sha256>(msg, &sig));
+        assert!(!pub_key.verify_pkcs1v15::<Sha256>(b"hello world!", &sig));
+
+        // CRT 算错 (这里直接改坏 dp) 时不能把错的签名返回出去
+        let mut bad = key.clone();
+        bad.dp += 1u8;
+        assert!(matches!(
+            bad.sign_pkcs1v15::<Sha256>(msg),
+            Err(RsaError::Fault)
+        ));
+        assert!(matches!(
+            bad.sign_pss::<Sha256>(msg, 32),
+            Err(RsaError::Fault)
+        ));
+
+        // 确定性签名, 和 openssl 的结果完全一致
+        let pkey = to_openssl(&key);
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        signer.update(msg).unwrap();
+        assert_eq!(signer.sign_to_vec().unwrap(), sig);
+    }
+
+    #[test]
+    fn test_pss() {
+        let key = RsaPrivateKey::new(1024).unwrap();
+        let pub_key = key.to_public_key();
+        let msg = b"hello world";
+        let sig = key.sign_pss::<Sha256>(msg, 32).unwrap();
+        assert!(pub_key.verify_pss::<Sha256>(msg, &sig, 32));
+        assert!(!pub_key.verify_pss::<Sha256>(msg, &sig, 20));
+        assert!(!pub_key.verify_pss::<Sha256>(b"hello world!", &sig, 32));
+
+        // salt 最长 em_len - h_len - 2, 太长 (包括加法溢出) 时报错而不是先分配内存或 panic
+        let max = key.size() - 32 - 2;
+        assert!(key.sign_pss::<Sha256>(msg, max).is_ok());
+        for salt_len in [max + 1, usize::MAX / 8 + 1, usize::MAX] {
+            assert!(matches!(
+                key.sign_pss::<Sha256>(msg, salt_len),
+                Err(RsaError::KeyTooShort(1024))
+            ));
+            assert!(!pub_key.verify_pss::<Sha256>(msg, &sig, salt_len));
+        }
+
+        let pkey = to_openssl(&key);
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        verifier.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        verifier
+            .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        verifier.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        verifier.update(msg).unwrap();
+        assert!(verifier.verify(&sig).unwrap());
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        signer
+            .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        signer.set_rsa_mgf1_md(MessageDigest::sha256()).unwrap();
+        signer.update(msg).unwrap();
+        assert!(pub_key.verify_pss::<Sha256>(msg, &signer.sign_to_vec().unwrap(), 32));
+    }
+
+    #[test]
+    fn test_key_format() {
+        let rsa = Rsa::generate(1024).unwrap();
+        let pkey = PKey::from_rsa(rsa.clone()).unwrap();
+
+        // PKCS#1
+        let der = rsa.private_key_to_der().unwrap();
+        let key = RsaPrivateKey::from_pkcs1_der(&der).unwrap();
+        assert_eq!(key.n.to_bytes_be(), rsa.n().to_vec());
+        assert_eq!(key.d.to_bytes_be(), rsa.d().to_vec());
+        assert_eq!(key.qinv.to_bytes_be(), rsa.iqmp().unwrap().to_vec());
+        assert_eq!(key.to_pkcs1_der(), der);
+        let pem = rsa.private_key_to_pem().unwrap();
+        let pem = String::from_utf8(pem).unwrap();
+        assert_eq!(RsaPrivateKey::from_pkcs1_pem(&pem).unwrap(), key);
+        assert_eq!(key.to_pkcs1_pem(), pem);
+
+        let pub_key = key.to_public_key();
+        let der = rsa.public_key_to_der_pkcs1().unwrap();
+        assert_eq!(RsaPublicKey::from_pkcs1_der(&der).unwrap(), pub_key);
+        assert_eq!(pub_key.to_pkcs1_der(), der);
+        let pem = rsa.public_key_to_pem_pkcs1().unwrap();
+        let pem = String::from_utf8(pem).unwrap();
+        assert_eq!(RsaPublicKey::from_pkcs1_pem(&pem).unwrap(), pub_key);
+        assert_eq!(pub_key.to_pkcs1_pem(), pem);
+
+        // SPKI
+        let der = rsa.public_key_to_der().unwrap();
+        assert_eq!(RsaPublicKey::from_public_key_der(&der).unwrap(), pub_key);
+        assert_eq!(pub_key.to_public_key_der(), der);
+        let pem = String::from_utf8(rsa.public_key_to_pem().unwrap()).unwrap();
+        assert_eq!(RsaPublicKey::from_public_key_pem(&pem).unwrap(), pub_key);
+        assert_eq!(pub_key.to_public_key_pem(), pem);
+
+        // PKCS#8
+        let pem = pkey.private_key_to_pem_pkcs8().unwrap();
+        let pem = String::from_utf8(pem).unwrap();
+        assert_eq!(RsaPrivateKey::from_pkcs8_pem(&pem).unwrap(), key);
+        assert_eq!(key.to_pkcs8_pem(), pem);
+        let der = PKey::private_key_from_pem(key.to_pkcs8_pem().as_bytes())
+            .unwrap()
+            .private_key_to_der()
+            .unwrap();
+        assert_eq!(RsaPrivateKey::from_pkcs1_der(&der).unwrap(), key);
+
+        // 格式错误
+        assert!(RsaPrivateKey::from_pkcs1_pem(&key.to_pkcs8_pem()).is_err());
+        assert!(RsaPublicKey::from_public_key_der(&pub_key.to_pkcs1_der()).is_err());
+        let mut der = pub_key.to_pkcs1_der();
+        der.push(0);
+        assert!(matches!(
+            RsaPublicKey::from_pkcs1_der(&der),
+            Err(DecodeKeyError::TrailingData(_))
+        ));
+
+        // 格式对但参数不一致
+        let uint = |x: u32| der::uint(&BigUint::from(x));
+        for (n, e) in [(0, 0), (0, 3), (3233, 0), (3233, 1), (3233, 3233), (1, 3)] {
+            let der = der::sequence(&[uint(n), uint(e)]);
+            assert!(matches!(
+                RsaPublicKey::from_pkcs1_der(&der),
+                Err(DecodeKeyError::InvalidKey)
+            ));
+        }
+        // 小的合法密钥: n = 61 * 53, e = 17, d = 413 (mod lcm)
+        let nums = |v: [u32; 8]| {
+            let mut items = vec![uint(0)];
+            items.extend(v.iter().map(|&x| uint(x)));
+            der::sequence(&items)
+        };
+        let good = [3233, 17, 413, 61, 53, 53, 49, 38];
+        assert!(RsaPrivateKey::from_pkcs1_der(&nums(good)).is_ok());
+        for i in 0..8 {
+            let mut bad = good;
+            bad[i] = if i == 3 || i == 4 { 0 } else { bad[i] + 1 };
+            assert!(matches!(
+                RsaPrivateKey::from_pkcs1_der(&nums(bad)),
+                Err(DecodeKeyError::InvalidKey)
+            ));
+        }
+        assert!(RsaPrivateKey::from_pkcs1_der(&nums([0; 8])).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! SM2 (GB/T 32918-2016): 数字签名和公钥加密, 哈希用 SM3; 曲线是 `Ec::sm2`,
+//! 其他曲线也能用, 不过标准只定义了推荐曲线
+
+use crate::conv::biguint_to_be;
+use crate::ecc::{calc_inv, mod_p, Ec, Point};
+use crate::error::EcError;
+use crate::hash::{Digest, Sm3};
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+
+/// 没有约定用户 ID 时的默认值 (GM/T 0009-2012)
+pub const DEFAULT_ID: &[u8] = b"1234567812345678";
+
+/// 密钥派生函数: SM3(Z || 1) || SM3(Z || 2) || ..., 截取前 len 字节
+fn kdf(z: &[u8], len: usize) -> Vec<u8> {
+    let mut res = Vec::with_capacity(len + Sm3::OUTPUT_LEN);
+    let mut ct = 1u32;
+    while res.len() < len {
+        let mut h = Sm3::new();
+        h.update(z);
+        h.update(&ct.to_be_bytes());
+        res.extend(h.finalize());
+        ct += 1;
+    }
+    res.truncate(len);
+    res
+}
+
+impl Ec {
+    /// 域元素的字节数
+    fn coord_len(&self) -> usize {
+        self.p.bits().div_ceil(8)
+    }
+
+    /// 域元素转成定长的 big endian
+    fn coord(&self, x: &BigInt) -> Vec<u8> {
+        let x = mod_p(x.clone(), &self.p).to_biguint().unwrap();
+        biguint_to_be(&x, self.coord_len()).unwrap()
+    }
+
+    /// ZA = SM3(ENTLA || ID || a || b || xG || yG || xA || yA), 签名时把用户 ID 和公钥也算进去;
+    /// ENTLA 是 ID 的比特长度 (2 字节), 所以 ID 不能超过 8191 字节
+    pub fn sm2_za(&self, id: &[u8], pub_key: &Point) -> Result<Vec<u8>, EcError> {
+        if id.len() >= 8192 {
+            return Err(EcError::IdTooLong(id.len()));
+        }
+        let mut h = Sm3::new();
+        h.update(&((id.len() * 8) as u16).to_be_bytes());
+        h.update(id);
+        for x in [
+            &self.a, &self.b, &self.g.x, &self.g.y, &pub_key.x, &pub_key.y,
+        ] {
+            h.update(&self.coord(x));
+        }
+        Ok(h.finalize())
+    }
+
+    /// e = SM3(ZA || M)
+    fn sm2_digest(&self, msg: &[u8], id: &[u8], pub_key: &Point) -> Result<BigInt, EcError> {
+        let mut h = Sm3::new();
+        h.update(&self.sm2_za(id, pub_key)?);
+        h.update(msg);
+        Ok(BigInt::from_bytes_be(Sign::Plus, &h.finalize()))
+    }
+
+    /// SM2 签名, 返回 (r, s); 私钥要在 [1, n - 2] (d = n - 1 时 1 + d 不可逆)
+    pub fn sm2_sign(
+        &self,
+        msg: &[u8],
+        id: &[u8],
+        pri_key: &BigInt,
+    ) -> Result<(BigInt, BigInt), EcError> {
+        if pri_key.sign() != Sign::Plus
+            || pri_key >= &(&self.n - 1u8)
+            || calc_inv(pri_key + 1, &self.n).is_none()
+        {
+            return Err(EcError::InvalidPrivateKey);
+        }
+        let pub_key = self.mul_secret(pri_key, &self.g);
+        let e = self.sm2_digest(msg, id, &pub_key)?;
+        loop {
+            if let Some(sig) = self.sm2_sign_with_k(&e, pri_key, &self.gen_pri_key()) {
+                return Ok(sig);
+            }
+        }
+    }
+
+    /// r = e + x1, s = (1 + d)^-1 * (k - r * d); r = 0, r + k = n, s = 0 时要换一个 k
+    fn sm2_sign_with_k(
+        &self,
+        e: &BigInt,
+        pri_key: &BigInt,
+        k: &BigInt,
+    ) -> Option<(BigInt, BigInt)> {
+        let p1 = self.mul_secret(k, &self.g);
+        let r = mod_p(e + &p1.x, &self.n);
+        if r.is_zero() || &r + k == self.n {
+            return None;
+        }
+        let s = calc_inv(pri_key + 1, &self.n)? * (k - &r * pri_key);
+        let s = mod_p(s, &self.n);
+        if s.is_zero() {
+            None
+        } else {
+            Some((r, s))
+        }
+    }
+
+    /// ID 太长也当作验签失败
+    pub fn sm2_verify(
+        &self,
+        msg: &[u8],
+        id: &[u8],
+        pub_key: &Point,
+        r: &BigInt,
+        s: &BigInt,
+    ) -> bool {
+        // r, s 都要在 [1, n - 1]
+        let in_range = |x: &BigInt| x.sign() == Sign::Plus && x < &self.n;
+        if !in_range(r) || !in_range(s) {
+            return false;
+        }
+        let t = mod_p(r + s, &self.n);
+        if t.is_zero() {
+            return false;
+        }
+        let e = match self.sm2_digest(msg, id, pub_key) {
+            Ok(e) => e,
+            Err(_) => return false,
+        };
+        let p1 = self.mul2(s, &self.g, &t, pub_key);
+        !p1.is_zero() && mod_p(e + &p1.x, &self.n) == *r
+    }
+
+    /// SM2 公钥加密, 输出 C1 || C3 || C2: C1 = kG (未压缩, 04 || x || y),
+    /// C2 = M ^ KDF(x2 || y2), C3 = SM3(x2 || M || y2), 其中 (x2, y2) = k * pub_key
+    pub fn sm2_encrypt(&self, msg: &[u8], pub_key: &Point) -> Result<Vec<u8>, EcError> {
+        if pub_key.is_zero() || !self.contains(pub_key) {
+            return Err(EcError::NotOnCurve);
+        }
+        loop {
+            if let Some(c) = self.sm2_encrypt_with_k(msg, pub_key, &self.gen_pri_key()) {
+                return Ok(c);
+            }
+        }
+    }
+
+    /// KDF 的输出全是 0 时要换一个 k
+    fn sm2_encrypt_with_k(&self, msg: &[u8], pub_key: &Point, k: &BigInt) -> Option<Vec<u8>> {
+        let c1 = self.mul_secret(k, &self.g);
+        let p2 = self.mul_secret(k, pub_key);
+        let (x2, y2) = (self.coord(&p2.x), self.coord(&p2.y));
+        let t = kdf(&[x2.as_slice(), &y2].concat(), msg.len());
+        if !msg.is_empty() && t.iter().all(|&b| b == 0) {
+            return None;
+        }
+        let mut h = Sm3::new();
+        h.update(&x2);
+        h.update(msg);
+        h.update(&y2);
+
+        let mut res = vec![0x04];
+        res.extend(self.coord(&c1.x));
+        res.extend(self.coord(&c1.y));
+        res.extend(h.finalize());
+        res.extend(msg.iter().zip(&t).map(|(m, t)| m ^ t));
+        Some(res)
+    }
+
+    /// 和 RSA 一样, 不区分失败的原因 (格式, 点不在曲线上, C3 不对)
+    pub fn sm2_decrypt(&self, cipher: &[u8], pri_key: &BigInt) -> Result<Vec<u8>, EcError> {
+        let len = self.coord_len();
+        let head = 1 + 2 * len;
+        if cipher.len() < head + Sm3::OUTPUT_LEN || cipher[0] != 0x04 {
+            return Err(EcError::Decryption);
+        }
+        let c1 = Point::new(
+            BigInt::from_bytes_be(Sign::Plus, &cipher[1..1 + len]),
+            BigInt::from_bytes_be(Sign::Plus, &cipher[1 + len..head]),
+        );
+        if c1.x >= self.p || c1.y >= self.p || !self.contains(&c1) {
+            return Err(EcError::Decryption);
+        }
+        let (c3, c2) = cipher[head..].split_at(Sm3::OUTPUT_LEN);
+
+        let p2 = self.mul_secret(pri_key, &c1);
+        if p2.is_zero() {
+            return Err(EcError::Decryption);
+        }
+        let (x2, y2) = (self.coord(&p2.x), self.coord(&p2.y));
+        let t = kdf(&[x2.as_slice(), &y2].concat(), c2.len());
+        if !c2.is_empty() && t.iter().all(|&b| b == 0) {
+            return Err(EcError::Decryption);
+        }
+        let msg: Vec<u8> = c2.iter().zip(&t).map(|(c, t)| c ^ t).collect();
+        let mut h = Sm3::new();
+        h.update(&x2);
+        h.update(&msg);
+        h.update(&y2);
+        if h.finalize() != c3 {
+            return Err(EcError::Decryption);
+        }
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conv::{bytes_to_hex, hex_to_bytes_lenient};
+    use crate::ecc::i_from_hex4;
+
+    fn key() -> (Ec, BigInt, Point) {
+        // GB/T 32918 示例用的私钥
+        let ec = Ec::sm2();
+        let d =
+            i_from_hex4("3945208F 7B2144B1 3F36E38A C6D39F95 88939369 2860B51A 42FB81EF 4DF7C5B8")
+                .unwrap();
+        let pub_key = ec.mul_secret(&d, &ec.g);
+        (ec, d, pub_key)
+    }
+
+    #[test]
+    fn test_sm2_sign() {
+        let (ec, d, pub_key) = key();
+        assert_eq!(
+            pub_key,
+            Point::new(
+                i_from_hex4(
+                    "09F9DF31 1E5421A1 50DD7D16 1E4BC5C6 72179FAD 1833FC07 6BB08FF3 56F35020"
+                )
+                .unwrap(),
+                i_from_hex4(
+                    "CCEA490C E26775A5 2DC6EA71 8CC1AA60 0AED05FB F35E084A 6632F607 2DA9AD13"
+                )
+                .unwrap(),
+            )
+        );
+        assert_eq!(
+            bytes_to_hex(&ec.sm2_za(DEFAULT_ID, &pub_key).unwrap()),
+            "b2e14c5c79c6df5b85f4fe7ed8db7a262b9da7e07ccb0ea9f4747b8ccda8a4f3"
+        );
+
+        // 固定 k 的签名
+        let msg = b"message digest";
+        let e = ec.sm2_digest(msg, DEFAULT_ID, &pub_key).unwrap();
+        let k =
+            i_from_hex4("59276E27 D506861A 16680F3A D9C02DCC EF3CC1FA 3CDBE4CE 6D54B80D EAC1BC21")
+                .unwrap();
+        let (r, s) = ec.sm2_sign_with_k(&e, &d, &k).unwrap();
+        assert_eq!(
+            r,
+            i_from_hex4("F5A03B06 48D2C463 0EEAC513 E1BB81A1 5944DA38 27D5B741 43AC7EAC EEE720B3")
+                .unwrap()
+        );
+        assert_eq!(
+            s,
+            i_from_hex4("B1B6AA29 DF212FD8 763182BC 0D421CA1 BB9038FD 1F7F42D4 840B69C4 85BBC1AA")
+                .unwrap()
+        );
+        assert!(ec.sm2_verify(msg, DEFAULT_ID, &pub_key, &r, &s));
+
+        // 随机 k; 消息, ID, 签名改了都要失败
+        let (r, s) = ec.sm2_sign(msg, b"ALICE123@YAHOO.COM", &d).unwrap();
+        assert!(ec.sm2_verify(msg, b"ALICE123@YAHOO.COM", &pub_key, &r, &s));
+        assert!(!ec.sm2_verify(msg, DEFAULT_ID, &pub_key, &r, &s));
+        assert!(!ec.sm2_verify(b"message digesT", b"ALICE123@YAHOO.COM", &pub_key, &r, &s));
+        assert!(!ec.sm2_verify(msg, b"ALICE123@YAHOO.COM", &pub_key, &(&r + 1u8), &s));
+        assert!(!ec.sm2_verify(msg, b"ALICE123@YAHOO.COM", &pub_key, &r, &BigInt::zero()));
+        assert!(!ec.sm2_verify(msg, b"ALICE123@YAHOO.COM", &pub_key, &r, &(&s + &ec.n)));
+
+        // 私钥不在 [1, n - 2] 时直接报错, 不会一直换 k
+        for bad in [BigInt::zero(), &ec.n - 1u8, ec.n.clone()] {
+            assert!(matches!(
+                ec.sm2_sign(msg, DEFAULT_ID, &bad),
+                Err(EcError::InvalidPrivateKey)
+            ));
+        }
+        assert!(ec.sm2_sign(msg, DEFAULT_ID, &(&ec.n - 2u8)).is_ok());
+
+        // ID 最长 8191 字节
+        let long_id = vec![b'a'; 8192];
+        assert!(matches!(
+            ec.sm2_za(&long_id, &pub_key),
+            Err(EcError::IdTooLong(8192))
+        ));
+        assert!(matches!(
+            ec.sm2_sign(msg, &long_id, &d),
+            Err(EcError::IdTooLong(8192))
+        ));
+        assert!(!ec.sm2_verify(msg, &long_id, &pub_key, &r, &s));
+        assert!(ec.sm2_za(&long_id[..8191], &pub_key).is_ok());
+    }
+
+    #[test]
+    fn test_sm2_encrypt() {
+        let (ec, d, pub_key) = key();
+        let msg = b"encryption standard";
+        let k =
+            i_from_hex4("59276E27 D506861A 16680F3A D9C02DCC EF3CC1FA 3CDBE4CE 6D54B80D EAC1BC21")
+                .unwrap();
+        let cipher = ec.sm2_encrypt_with_k(msg, &pub_key, &k).unwrap();
+        let expected = hex_to_bytes_lenient(
+            "04 04EBFC718E8D1798620432268E77FEB6415E2EDE0E073C0F4F640ECD2E149A73 \
+             E858F9D81E5430A57B36DAAB8F950A3C64E6EE6A63094D99283AFF767E124DF0 \
+             59983C18F809E262923C53AEC295D30383B54E39D609D160AFCB1908D0BD8766 \
+             21886CA989CA9C7D58087307CA93092D651EFA",
+        )
+        .unwrap();
+        assert_eq!(cipher, expected);
+        assert_eq!(ec.sm2_decrypt(&cipher, &d).unwrap(), msg);
+
+        // 随机 k, 包括空消息和超过一个 SM3 输出长度的消息
+        for msg in [&b""[..], b"a", &[0x5a; 100]] {
+            let cipher = ec.sm2_encrypt(msg, &pub_key).unwrap();
+            assert_eq!(cipher.len(), 1 + 64 + 32 + msg.len());
+            assert_eq!(ec.sm2_decrypt(&cipher, &d).unwrap(), msg);
+        }
+
+        // 改了任何一部分都要失败
+        let cipher = ec.sm2_encrypt(msg, &pub_key).unwrap();
+        for i in [0, 1, 70, 100, cipher.len() - 1] {
+            let mut c = cipher.clone();
+            c[i] ^= 1;
+            assert!(matches!(ec.sm2_decrypt(&c, &d), Err(EcError::Decryption)));
+        }
+        assert!(ec.sm2_decrypt(&cipher[..96], &d).is_err());
+        assert!(ec.sm2_decrypt(&cipher, &(&d + 1u8)).is_err());
+        assert!(matches!(
+            ec.sm2_encrypt(msg, &Point::new_i32(1, 1)),
+            Err(EcError::NotOnCurve)
+        ));
+    }
+}